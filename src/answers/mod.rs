@@ -0,0 +1,369 @@
+//! Known answers and regression checking
+//!
+//! Answers depend on the input, so the answers file records them per input: every line holds the day, the part,
+//! the hash of the input and the answer, separated by whitespace. Lines starting with '#' are comments. Answers
+//! that span multiple lines (rendered letters) have their line breaks escaped as `\n`.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::panic;
+use std::path::Path;
+
+use crate::common::{input_hash, load, InputError};
+use crate::{day1, day10, day11, day12, day13, day14, day15, day16, day17};
+use crate::{day2, day3, day4, day5, day6, day7, day8, day9};
+
+pub const ANSWERS_FILE: &str = "answers.txt";
+
+/// Conversion of the result of a solution into the text of an answer, None when there is no answer
+pub trait Answer {
+    fn answer(self) -> Option<String>;
+}
+
+macro_rules! answer {
+    ($($t:ty),*) => ($(
+        impl Answer for $t {
+            fn answer(self) -> Option<String> {
+                Some(self.to_string())
+            }
+        }
+    )*);
+}
+
+answer!(i32, i64, u32, u64, usize, String);
+
+impl<T: Answer> Answer for Option<T> {
+    fn answer(self) -> Option<String> {
+        self.and_then(Answer::answer)
+    }
+}
+
+/// Solution of a part of a day
+pub struct Solution {
+    pub day: u32,
+    pub part: u32,
+    pub solve: fn(&str) -> Option<String>,
+}
+
+macro_rules! solutions {
+    ($(($day:expr, $part:expr, $solve:path)),* $(,)?) => {
+        vec![$(Solution {
+            day: $day,
+            part: $part,
+            solve: |input| $solve(input).answer(),
+        }),*]
+    };
+}
+
+/// All solutions that return their answer, in order
+pub fn solutions() -> Vec<Solution> {
+    solutions![
+        (1, 1, day1::solve1),
+        (1, 2, day1::solve2),
+        (2, 1, day2::solve1),
+        (2, 2, day2::solve2),
+        (3, 1, day3::solve1),
+        (3, 2, day3::solve2),
+        (4, 1, day4::solve1),
+        (4, 2, day4::solve2),
+        (5, 1, day5::solve1),
+        (5, 2, day5::solve2),
+        (6, 1, day6::solve1),
+        (6, 2, day6::solve2),
+        (7, 1, day7::solve1),
+        (7, 2, day7::solve2),
+        (8, 1, day8::solve1),
+        (8, 2, day8::solve2),
+        (9, 1, day9::solve1),
+        (9, 2, day9::solve2),
+        (10, 1, day10::solve1),
+        (10, 2, day10::solve2),
+        (11, 1, day11::solve1),
+        (11, 2, day11::solve2),
+        (12, 1, day12::solve1),
+        (12, 2, day12::solve2),
+        (13, 1, day13::solve1),
+        (13, 2, day13::solve2),
+        (14, 1, day14::solve1),
+        (14, 2, day14::solve2),
+        (15, 1, day15::solve1),
+        (15, 2, day15::solve2),
+        (16, 1, day16::solve1),
+        (17, 1, day17::solve1),
+        (17, 2, day17::solve2),
+    ]
+}
+
+/// Answer as a single line: trailing whitespace of every line and trailing empty lines removed, line breaks escaped
+pub fn normalize(answer: &str) -> String {
+    answer
+        .lines()
+        .map(|line| line.trim_end())
+        .collect::<Vec<&str>>()
+        .join("\n")
+        .trim_end_matches('\n')
+        .replace('\\', "\\\\")
+        .replace('\n', "\\n")
+}
+
+/// Answers by day, part and input hash
+#[derive(Debug, Default, PartialEq)]
+pub struct Answers {
+    answers: BTreeMap<(u32, u32, String), String>,
+}
+
+impl Answers {
+    pub fn parse(text: &str) -> Result<Answers, String> {
+        let mut answers = Answers::default();
+
+        for (ix, line) in text.lines().enumerate() {
+            // Answers may start with whitespace, so only the separator in front of them is removed
+            let line = line.trim_end();
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            // Day, part and hash may be separated by any whitespace, the answer is kept as it is
+            let mut fields = Vec::new();
+            let mut rest = line;
+            for _ in 0..3 {
+                let field = rest.trim_start();
+                match field.find(char::is_whitespace) {
+                    Some(end) => {
+                        fields.push(&field[..end]);
+                        rest = &field[end..];
+                    }
+                    None => {
+                        return Err(format!(
+                            "line {}: expected day, part, hash and answer",
+                            ix + 1
+                        ))
+                    }
+                }
+            }
+            let separator = rest.chars().next().map_or(0, char::len_utf8);
+            fields.push(&rest[separator..]);
+
+            let number = |field: &str| {
+                field
+                    .parse::<u32>()
+                    .map_err(|e| format!("line {}: {} ({:?})", ix + 1, e, field))
+            };
+
+            answers.insert(number(fields[0])?, number(fields[1])?, fields[2], fields[3]);
+        }
+
+        Ok(answers)
+    }
+
+    /// Answers in the file, none when the file does not exist yet
+    pub fn read(path: &Path) -> Result<Answers, String> {
+        match fs::read_to_string(path) {
+            Ok(text) => Answers::parse(&text).map_err(|e| format!("{}:{}", path.display(), e)),
+            Err(ref e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Answers::default()),
+            Err(e) => Err(format!("Could not read {}: {}", path.display(), e)),
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> std::io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    /// The normalized answer for the input with the given hash
+    pub fn get(&self, day: u32, part: u32, hash: &str) -> Option<&String> {
+        self.answers.get(&(day, part, hash.to_string()))
+    }
+
+    /// Adds or replaces an answer, which must be normalized
+    pub fn insert(&mut self, day: u32, part: u32, hash: &str, answer: &str) {
+        self.answers
+            .insert((day, part, hash.to_string()), answer.to_string());
+    }
+}
+
+impl fmt::Display for Answers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# day part input-hash answer")?;
+        for ((day, part, hash), answer) in self.answers.iter() {
+            writeln!(f, "{} {} {} {}", day, part, hash, answer)?;
+        }
+        Ok(())
+    }
+}
+
+pub enum Outcome {
+    Pass,
+    Fail {
+        expected: String,
+        actual: String,
+    },
+    /// No answer is known for this input yet
+    Unknown {
+        actual: String,
+    },
+    /// The solution found no answer or panicked
+    Error(String),
+    MissingInput(InputError),
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Pass => write!(f, "pass"),
+            Outcome::Fail { expected, actual } => {
+                write!(f, "FAIL expected {} but was {}", expected, actual)
+            }
+            Outcome::Unknown { actual } => write!(f, "unknown {}", actual),
+            Outcome::Error(message) => write!(f, "ERROR {}", message),
+            Outcome::MissingInput(error) => write!(f, "no input, {}", error),
+        }
+    }
+}
+
+/// Compares the answer of a solution for an input with the known answer
+pub fn check(solution: &Solution, input: &str, answers: &Answers) -> Outcome {
+    let solve = solution.solve;
+    let actual = match panic::catch_unwind(|| solve(input)) {
+        Ok(Some(answer)) => normalize(&answer),
+        Ok(None) => return Outcome::Error("no answer found".to_string()),
+        Err(cause) => {
+            let message = cause
+                .downcast_ref::<String>()
+                .cloned()
+                .or_else(|| cause.downcast_ref::<&str>().map(|s| s.to_string()))
+                .unwrap_or_else(|| "panicked".to_string());
+            return Outcome::Error(message);
+        }
+    };
+
+    match answers.get(solution.day, solution.part, &input_hash(input)) {
+        Some(expected) if *expected == actual => Outcome::Pass,
+        Some(expected) => Outcome::Fail {
+            expected: expected.clone(),
+            actual,
+        },
+        None => Outcome::Unknown { actual },
+    }
+}
+
+/// Runs the solutions of the given days (all days when empty) against the answers file and reports the outcome of
+/// every part. With record the unknown answers are added to the file. Returns false when any part failed
+pub fn verify(days: &[u32], record: bool) -> bool {
+    let path = Path::new(ANSWERS_FILE);
+    let mut answers = Answers::read(path).unwrap_or_else(|e| panic!("{}", e));
+
+    let mut counts = BTreeMap::new();
+    let mut recorded = 0;
+    for solution in solutions() {
+        if !days.is_empty() && !days.contains(&solution.day) {
+            continue;
+        }
+
+        let outcome = match load(solution.day) {
+            Ok(input) => {
+                let outcome = check(&solution, &input, &answers);
+                if let (true, Outcome::Unknown { actual }) = (record, &outcome) {
+                    answers.insert(solution.day, solution.part, &input_hash(&input), actual);
+                    recorded += 1;
+                }
+                outcome
+            }
+            Err(error) => Outcome::MissingInput(error),
+        };
+
+        println!("Day {:2} part {}: {}", solution.day, solution.part, outcome);
+
+        let kind = match outcome {
+            Outcome::Pass => "passed",
+            Outcome::Fail { .. } | Outcome::Error(_) => "failed",
+            Outcome::Unknown { .. } => "unknown",
+            Outcome::MissingInput(_) => "without input",
+        };
+        *counts.entry(kind).or_insert(0) += 1;
+    }
+
+    if recorded > 0 {
+        answers
+            .write(path)
+            .unwrap_or_else(|e| panic!("Could not write {}: {}", path.display(), e));
+        println!("Recorded {} answers in {}", recorded, path.display());
+    }
+
+    println!(
+        "{}",
+        counts
+            .iter()
+            .map(|(kind, count)| format!("{} {}", count, kind))
+            .collect::<Vec<String>>()
+            .join(", ")
+    );
+
+    !counts.contains_key("failed")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answers_file_round_trip() {
+        let mut answers = Answers::default();
+        answers.insert(8, 2, "0123", &normalize(" #  #  \n#### \n\n"));
+        answers.insert(1, 1, "abcd", "3481005");
+
+        let text = answers.to_string();
+        assert_eq!(
+            text,
+            "# day part input-hash answer\n1 1 abcd 3481005\n8 2 0123  #  #\\n####\n"
+        );
+        assert_eq!(Answers::parse(&text).unwrap(), answers);
+        assert_eq!(answers.get(8, 2, "0123"), Some(&" #  #\\n####".to_string()));
+
+        assert!(Answers::parse("1 x abcd 12")
+            .unwrap_err()
+            .starts_with("line 1:"));
+        assert!(Answers::parse("# comment\n1 1 abcd").is_err());
+    }
+
+    #[test]
+    fn aligned_answers_file() {
+        let answers = Answers::parse(" 1\t1   abcd 3481005\n10 2\t0123\t #  #\n").unwrap();
+        assert_eq!(answers.get(1, 1, "abcd"), Some(&"3481005".to_string()));
+        assert_eq!(answers.get(10, 2, "0123"), Some(&" #  #".to_string()));
+    }
+
+    #[test]
+    fn outcomes() {
+        let solution = Solution {
+            day: 1,
+            part: 1,
+            solve: |input| day1::solve1(input).answer(),
+        };
+        let mut answers = Answers::default();
+
+        match check(&solution, "12\n14", &answers) {
+            Outcome::Unknown { actual } => assert_eq!(actual, "4"),
+            outcome => panic!("Expected unknown, got {}", outcome),
+        }
+
+        answers.insert(1, 1, &input_hash("12\n14"), "4");
+        assert!(matches!(
+            check(&solution, "12\n14", &answers),
+            Outcome::Pass
+        ));
+
+        answers.insert(1, 1, &input_hash("12\n14"), "5");
+        assert!(matches!(
+            check(&solution, "12\n14", &answers),
+            Outcome::Fail { .. }
+        ));
+
+        let broken = Solution {
+            day: 1,
+            part: 2,
+            solve: |_| None,
+        };
+        assert!(matches!(check(&broken, "", &answers), Outcome::Error(_)));
+    }
+}
@@ -0,0 +1,373 @@
+//! Benchmarks of the solutions
+//!
+//! Every solution is run a number of times after warming up, the minimum and median times are reported. Where the
+//! input of a day can be parsed on its own the parse time is measured separately, the solve time is the total time
+//! minus the parse time. Reports are written as Markdown for reading and JSON for comparing with later runs.
+
+use std::fs;
+use std::hint::black_box;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::answers::solutions;
+use crate::common::load;
+use crate::cpu;
+use crate::{day1, day10, day12, day14, day16, day3, day4, day6, day8};
+
+pub struct Options {
+    pub warmup: usize,
+    pub iterations: usize,
+    /// Measuring a solution stops early when this is spent, after at least one iteration
+    pub budget: Duration,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            warmup: 1,
+            iterations: 5,
+            budget: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Parser of the input of a day, None when parsing is not separate from solving
+fn parser(day: u32) -> Option<fn(&str)> {
+    let parse: fn(&str) = match day {
+        1 => |input| {
+            black_box(day1::masses(input));
+        },
+        2 | 5 | 7 | 9 | 11 | 13 | 15 | 17 => |input| {
+            black_box(cpu::read(input));
+        },
+        3 => |input| {
+            black_box(day3::wires(input));
+        },
+        4 => |input| {
+            black_box(day4::password_range(input));
+        },
+        6 => |input| {
+            black_box(day6::build_body_tree(input));
+        },
+        8 => |input| {
+            black_box(day8::Bitmap::parse(input, 25, 6));
+        },
+        10 => |input| {
+            black_box(day10::parse_asteroids(&day10::lines(input)));
+        },
+        12 => |input| {
+            black_box(day12::moons(input));
+        },
+        14 => |input| {
+            black_box(day14::parse_reactions(input));
+        },
+        16 => |input| {
+            black_box(day16::parse_input(input));
+        },
+        _ => return None,
+    };
+    Some(parse)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+}
+
+impl Stats {
+    /// Statistics of the given times, which must not be empty
+    pub fn new(mut times: Vec<Duration>) -> Stats {
+        times.sort();
+        let middle = times.len() / 2;
+        let median = if times.len() % 2 == 1 {
+            times[middle]
+        } else {
+            (times[middle - 1] + times[middle]) / 2
+        };
+
+        Stats {
+            min: times[0],
+            median,
+        }
+    }
+}
+
+/// Times a function according to the options
+pub fn measure<F: FnMut()>(mut f: F, options: &Options) -> Stats {
+    for _ in 0..options.warmup {
+        f();
+    }
+
+    let start = Instant::now();
+    let mut times = Vec::new();
+    while times.len() < options.iterations.max(1)
+        && (times.is_empty() || start.elapsed() < options.budget)
+    {
+        let run = Instant::now();
+        f();
+        times.push(run.elapsed());
+    }
+
+    Stats::new(times)
+}
+
+/// Times of a single solution in nanoseconds
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Measurement {
+    pub day: u32,
+    pub part: u32,
+    pub iterations: usize,
+    pub parse_median_ns: Option<u64>,
+    pub median_ns: u64,
+    pub min_ns: u64,
+}
+
+impl Measurement {
+    /// Median time spent after parsing
+    pub fn solve_median_ns(&self) -> u64 {
+        self.median_ns
+            .saturating_sub(self.parse_median_ns.unwrap_or(0))
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Report {
+    pub measurements: Vec<Measurement>,
+}
+
+/// Change in the median time of a solution compared to an earlier report
+pub struct Comparison {
+    pub day: u32,
+    pub part: u32,
+    pub before_ns: u64,
+    /// Relative change in percent, positive is slower
+    pub change: f64,
+    pub regression: bool,
+}
+
+fn format_ns(ns: u64) -> String {
+    let ns = ns as f64;
+    if ns >= 1e9 {
+        format!("{:.2} s", ns / 1e9)
+    } else if ns >= 1e6 {
+        format!("{:.2} ms", ns / 1e6)
+    } else if ns >= 1e3 {
+        format!("{:.2} µs", ns / 1e3)
+    } else {
+        format!("{} ns", ns)
+    }
+}
+
+impl Report {
+    pub fn read(path: &Path) -> Result<Report, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+        serde_json::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Report can always be serialized")
+    }
+
+    /// Compares the medians with those in an earlier report, a regression is a slowdown by more than the threshold
+    /// in percent. Solutions that are not in both reports are skipped
+    pub fn compare(&self, baseline: &Report, threshold: f64) -> Vec<Comparison> {
+        self.measurements
+            .iter()
+            .filter_map(|after| {
+                let before = baseline
+                    .measurements
+                    .iter()
+                    .find(|m| m.day == after.day && m.part == after.part)?;
+                let change = if before.median_ns == 0 {
+                    0.0
+                } else {
+                    (after.median_ns as f64 / before.median_ns as f64 - 1.0) * 100.0
+                };
+
+                Some(Comparison {
+                    day: after.day,
+                    part: after.part,
+                    before_ns: before.median_ns,
+                    change,
+                    regression: change > threshold,
+                })
+            })
+            .collect()
+    }
+
+    /// Table of all measurements, with a column for the comparison when there is one
+    pub fn to_markdown(&self, comparisons: &[Comparison]) -> String {
+        let mut table = String::new();
+
+        table.push_str("| Day | Part | Parse | Solve | Median | Min | Runs |");
+        if !comparisons.is_empty() {
+            table.push_str(" Previous | Change |");
+        }
+        table.push_str("\n|----:|-----:|------:|------:|-------:|----:|-----:|");
+        if !comparisons.is_empty() {
+            table.push_str("---------:|-------:|");
+        }
+        table.push('\n');
+
+        for m in self.measurements.iter() {
+            table.push_str(&format!(
+                "| {} | {} | {} | {} | {} | {} | {} |",
+                m.day,
+                m.part,
+                m.parse_median_ns.map_or("-".to_string(), format_ns),
+                format_ns(m.solve_median_ns()),
+                format_ns(m.median_ns),
+                format_ns(m.min_ns),
+                m.iterations
+            ));
+
+            if !comparisons.is_empty() {
+                match comparisons
+                    .iter()
+                    .find(|c| c.day == m.day && c.part == m.part)
+                {
+                    Some(c) => table.push_str(&format!(
+                        " {} | {:+.1}%{} |",
+                        format_ns(c.before_ns),
+                        c.change,
+                        if c.regression { " **regression**" } else { "" }
+                    )),
+                    None => table.push_str(" - | - |"),
+                }
+            }
+            table.push('\n');
+        }
+
+        table
+    }
+}
+
+/// Benchmarks the solutions of the given days, all days when empty. Days without an input are skipped
+pub fn run(days: &[u32], options: &Options) -> Report {
+    let mut report = Report::default();
+
+    for solution in solutions() {
+        if !days.is_empty() && !days.contains(&solution.day) {
+            continue;
+        }
+
+        let input = match load(solution.day) {
+            Ok(input) => input,
+            Err(e) => {
+                eprintln!(
+                    "Skipping day {} part {}: {}",
+                    solution.day, solution.part, e
+                );
+                continue;
+            }
+        };
+
+        let parse = parser(solution.day).map(|parse| measure(|| parse(&input), options));
+
+        let solve = solution.solve;
+        let mut iterations = 0;
+        let total = measure(
+            || {
+                black_box(solve(&input));
+                iterations += 1;
+            },
+            options,
+        );
+
+        let measurement = Measurement {
+            day: solution.day,
+            part: solution.part,
+            iterations: iterations - options.warmup,
+            parse_median_ns: parse.map(|p| p.median.as_nanos() as u64),
+            median_ns: total.median.as_nanos() as u64,
+            min_ns: total.min.as_nanos() as u64,
+        };
+        eprintln!(
+            "Day {:2} part {}: {}",
+            measurement.day,
+            measurement.part,
+            format_ns(measurement.median_ns)
+        );
+        report.measurements.push(measurement);
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn measurement(day: u32, part: u32, median_ns: u64) -> Measurement {
+        Measurement {
+            day,
+            part,
+            iterations: 3,
+            parse_median_ns: Some(100),
+            median_ns,
+            min_ns: median_ns / 2,
+        }
+    }
+
+    #[test]
+    fn statistics() {
+        let ms = Duration::from_millis;
+
+        let stats = Stats::new(vec![ms(5), ms(1), ms(9)]);
+        assert_eq!(stats.min, ms(1));
+        assert_eq!(stats.median, ms(5));
+        assert_eq!(Stats::new(vec![ms(4), ms(2), ms(8), ms(1)]).median, ms(3));
+
+        let mut runs = 0;
+        let options = Options {
+            warmup: 2,
+            iterations: 3,
+            budget: Duration::from_secs(10),
+        };
+        measure(|| runs += 1, &options);
+        assert_eq!(runs, 5);
+
+        // A spent budget still measures once
+        runs = 0;
+        let options = Options {
+            warmup: 0,
+            iterations: 3,
+            budget: Duration::from_secs(0),
+        };
+        measure(|| runs += 1, &options);
+        assert_eq!(runs, 1);
+    }
+
+    #[test]
+    fn reports_round_trip_and_compare() {
+        let baseline = Report {
+            measurements: vec![measurement(1, 1, 1000), measurement(1, 2, 1000)],
+        };
+        let report = Report {
+            measurements: vec![
+                measurement(1, 1, 1050),
+                measurement(1, 2, 2000),
+                measurement(2, 1, 10),
+            ],
+        };
+        assert_eq!(
+            serde_json::from_str::<Report>(&report.to_json()).unwrap(),
+            report
+        );
+
+        let comparisons = report.compare(&baseline, 10.0);
+        assert_eq!(comparisons.len(), 2);
+        assert!(!comparisons[0].regression);
+        assert!(comparisons[1].regression);
+        assert_eq!(comparisons[1].change, 100.0);
+
+        let table = report.to_markdown(&comparisons);
+        assert_eq!(table.lines().count(), 5);
+        assert!(table.contains("| 1 | 2 | 100 ns | 1.90 µs | 2.00 µs | 1.00 µs | 3 | 1.00 µs | +100.0% **regression** |"));
+        assert!(table.lines().last().unwrap().ends_with("| - | - |"));
+    }
+}
@@ -0,0 +1,269 @@
+//! Downloading of puzzle inputs
+//!
+//! Inputs are personal, so downloading requires the session token of a logged in user (the value of the session
+//! cookie). Downloaded inputs are cached as `dayN.txt` files, by default in the input directory so they are picked
+//! up by `input::load`. The MockServer stands in for the real server when testing offline.
+
+use std::env;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use super::input::{input_dir, InputError};
+
+/// Environment variable with the session token, downloading is disabled without it
+pub const SESSION_VAR: &str = "ADVENT_SESSION";
+
+/// Environment variable that overrides the base URL, e.g. to use a MockServer
+pub const BASE_URL_VAR: &str = "ADVENT_URL";
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com/2019";
+
+pub struct Fetcher {
+    base_url: String,
+    session: String,
+    cache: PathBuf,
+}
+
+impl Fetcher {
+    pub fn new(base_url: &str, session: &str, cache: &Path) -> Fetcher {
+        Fetcher {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            session: session.to_string(),
+            cache: cache.to_path_buf(),
+        }
+    }
+
+    /// Fetcher configured by the environment that caches in the input directory, None when there is no session token
+    pub fn from_env() -> Option<Fetcher> {
+        let session = env::var(SESSION_VAR).ok().filter(|s| !s.is_empty())?;
+        let base_url = env::var(BASE_URL_VAR).unwrap_or_else(|_| DEFAULT_BASE_URL.to_string());
+
+        Some(Fetcher::new(&base_url, &session, &input_dir()))
+    }
+
+    pub fn url(&self, day: u32) -> String {
+        format!("{}/day/{}/input", self.base_url, day)
+    }
+
+    pub fn cache_path(&self, day: u32) -> PathBuf {
+        self.cache.join(format!("day{}.txt", day))
+    }
+
+    /// Downloads the input of a day, without caching it
+    pub fn fetch(&self, day: u32) -> Result<String, InputError> {
+        let url = self.url(day);
+        let error = |message: String| InputError::Fetch {
+            url: url.clone(),
+            message,
+        };
+
+        let response = ureq::get(&url)
+            .timeout(Duration::from_secs(30))
+            .set("Cookie", &format!("session={}", self.session))
+            .set("User-Agent", "advent_2019 input fetcher")
+            .call()
+            .map_err(|e| match e {
+                ureq::Error::Status(status, response) => {
+                    error(format!("HTTP {} {}", status, response.status_text()))
+                }
+                e => error(e.to_string()),
+            })?;
+
+        response.into_string().map_err(|e| error(e.to_string()))
+    }
+
+    /// Location of the cached input of a day, which is downloaded first when it is missing or empty
+    pub fn ensure(&self, day: u32) -> Result<PathBuf, InputError> {
+        let path = self.cache_path(day);
+        let cached = fs::metadata(&path).map(|m| m.len() > 0).unwrap_or(false);
+
+        if !cached {
+            let input = self.fetch(day)?;
+            let io_error = |error| InputError::Io {
+                path: path.clone(),
+                error,
+            };
+
+            fs::create_dir_all(&self.cache).map_err(io_error)?;
+            fs::write(&path, input).map_err(io_error)?;
+        }
+
+        Ok(path)
+    }
+}
+
+/// Minimal HTTP server that serves `dayN.txt` files from a fixture directory as `/day/N/input`, only to requests
+/// with the expected session cookie. It stops when dropped
+pub struct MockServer {
+    address: SocketAddr,
+    requests: Arc<AtomicUsize>,
+    stop: Arc<AtomicBool>,
+}
+
+impl MockServer {
+    /// Starts serving on the given port of localhost, port 0 picks a free port
+    pub fn start(fixtures: &Path, session: &str, port: u16) -> std::io::Result<MockServer> {
+        let listener = TcpListener::bind(("127.0.0.1", port))?;
+        let address = listener.local_addr()?;
+        let requests = Arc::new(AtomicUsize::new(0));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let fixtures = fixtures.to_path_buf();
+        let cookie = format!("session={}", session);
+        let (count, stopped) = (requests.clone(), stop.clone());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if stopped.load(Ordering::SeqCst) {
+                    break;
+                }
+                if let Ok(stream) = stream {
+                    count.fetch_add(1, Ordering::SeqCst);
+                    // A client that hangs up halfway is not a reason to stop serving
+                    let _ = MockServer::respond(stream, &fixtures, &cookie);
+                }
+            }
+        });
+
+        Ok(MockServer {
+            address,
+            requests,
+            stop,
+        })
+    }
+
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    /// Number of requests received so far
+    pub fn requests(&self) -> usize {
+        self.requests.load(Ordering::SeqCst)
+    }
+
+    fn respond(stream: TcpStream, fixtures: &Path, cookie: &str) -> std::io::Result<()> {
+        let mut reader = BufReader::new(stream);
+
+        let mut request = String::new();
+        reader.read_line(&mut request)?;
+
+        let mut authorized = false;
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                if name.eq_ignore_ascii_case("cookie") {
+                    authorized |= value.split(';').any(|c| c.trim() == cookie);
+                }
+            }
+        }
+
+        // GET /day/<N>/input HTTP/1.1
+        let day = request.split_whitespace().nth(1).and_then(|path| {
+            let parts = path.split('/').collect::<Vec<&str>>();
+            match parts.as_slice() {
+                ["", "day", day, "input"] => day.parse::<u32>().ok(),
+                _ => None,
+            }
+        });
+
+        let (status, body) = match day {
+            _ if !authorized => (
+                "400 Bad Request",
+                "Puzzle inputs differ by user.  Please log in to get your puzzle input.\n"
+                    .to_string(),
+            ),
+            Some(day) => match fs::read_to_string(fixtures.join(format!("day{}.txt", day))) {
+                Ok(input) => ("200 OK", input),
+                Err(_) => ("404 Not Found", "404 Not Found\n".to_string()),
+            },
+            None => ("404 Not Found", "404 Not Found\n".to_string()),
+        };
+
+        let mut stream = reader.into_inner();
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        )?;
+        stream.flush()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        // Wake up the server thread that is waiting for a connection
+        let _ = TcpStream::connect(self.address);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("advent-cache-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn fetches_and_caches_inputs() {
+        let server = MockServer::start(Path::new("inputs"), "secret", 0).unwrap();
+        let cache = cache_dir("fetch");
+        let fetcher = Fetcher::new(&server.base_url(), "secret", &cache);
+
+        let path = fetcher.ensure(9).unwrap();
+        assert_eq!(path, cache.join("day9.txt"));
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            fs::read_to_string("inputs/day9.txt").unwrap()
+        );
+        assert_eq!(server.requests(), 1);
+
+        // Cached, so no second request
+        fetcher.ensure(9).unwrap();
+        assert_eq!(server.requests(), 1);
+
+        // Empty cache files are filled
+        fs::write(cache.join("day13.txt"), "").unwrap();
+        fetcher.ensure(13).unwrap();
+        assert_eq!(server.requests(), 2);
+        assert!(fs::metadata(cache.join("day13.txt")).unwrap().len() > 0);
+
+        fs::remove_dir_all(&cache).unwrap();
+    }
+
+    #[test]
+    fn reports_refused_and_missing_inputs() {
+        let server = MockServer::start(Path::new("inputs"), "secret", 0).unwrap();
+        let cache = cache_dir("errors");
+
+        let error = Fetcher::new(&server.base_url(), "wrong", &cache)
+            .ensure(9)
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Could not fetch {}/day/9/input: HTTP 400 Bad Request",
+                server.base_url()
+            )
+        );
+
+        let error = Fetcher::new(&server.base_url(), "secret", &cache)
+            .ensure(25)
+            .unwrap_err();
+        assert!(error.to_string().ends_with("HTTP 404 Not Found"));
+        assert!(!cache.join("day25.txt").exists());
+    }
+}
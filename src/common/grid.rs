@@ -0,0 +1,462 @@
+use std::collections::HashMap;
+use std::iter::FromIterator;
+
+use super::Point;
+
+/// Which cells count as neighbors of a cell
+#[derive(Clone, Copy, PartialEq)]
+pub enum Adjacency {
+    /// North, east, south and west
+    Four,
+    /// Including the diagonals
+    Eight,
+}
+
+impl Adjacency {
+    pub fn offsets(&self) -> &'static [(i32, i32)] {
+        match self {
+            Adjacency::Four => &[(0, -1), (1, 0), (0, 1), (-1, 0)],
+            Adjacency::Eight => &[
+                (0, -1),
+                (1, -1),
+                (1, 0),
+                (1, 1),
+                (0, 1),
+                (-1, 1),
+                (-1, 0),
+                (-1, -1),
+            ],
+        }
+    }
+}
+
+enum Storage<T> {
+    /// Only cells that are set are stored, suited for maps that are explored or grow in any direction
+    Sparse(HashMap<Point, T>),
+    /// All cells within the extends are stored row by row, suited for maps with a (mostly) known size
+    Dense {
+        origin: Point,
+        width: usize,
+        height: usize,
+        cells: Vec<Option<T>>,
+    },
+}
+
+/// Two dimensional map of cells
+pub struct Grid<T> {
+    storage: Storage<T>,
+    extends: Option<(Point, Point)>,
+    len: usize,
+}
+
+impl<T> Default for Grid<T> {
+    fn default() -> Self {
+        Grid::new()
+    }
+}
+
+impl<T> Grid<T> {
+    /// Sparse grid without any cells
+    pub fn new() -> Grid<T> {
+        Grid {
+            storage: Storage::Sparse(HashMap::new()),
+            extends: None,
+            len: 0,
+        }
+    }
+
+    /// Dense grid with room for all cells from top left to bottom right, it grows when cells outside are set
+    pub fn dense(top_left: &Point, bottom_right: &Point) -> Grid<T> {
+        let width = (bottom_right.x - top_left.x + 1).max(0) as usize;
+        let height = (bottom_right.y - top_left.y + 1).max(0) as usize;
+
+        Grid {
+            storage: Storage::Dense {
+                origin: *top_left,
+                width,
+                height,
+                cells: (0..width * height).map(|_| None).collect(),
+            },
+            extends: None,
+            len: 0,
+        }
+    }
+
+    fn index(origin: &Point, width: usize, height: usize, location: &Point) -> Option<usize> {
+        let x = location.x - origin.x;
+        let y = location.y - origin.y;
+
+        if x >= 0 && y >= 0 && (x as usize) < width && (y as usize) < height {
+            Some(y as usize * width + x as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Reallocates a dense grid so it also covers location. Every side that has to move out grows by at least the
+    /// current width or height, so filling a grid one row or column at a time only reallocates a few times
+    fn grow(&mut self, location: &Point) {
+        if let Storage::Dense {
+            origin,
+            width,
+            height,
+            cells,
+        } = &mut self.storage
+        {
+            if Self::index(origin, *width, *height, location).is_some() {
+                return;
+            }
+
+            let extra = |needed: i32, size: usize| {
+                if needed > 0 {
+                    needed.max(size as i32)
+                } else {
+                    0
+                }
+            };
+            let right = origin.x + *width as i32 - 1;
+            let bottom = origin.y + *height as i32 - 1;
+            let top_left = Point::new(
+                origin.x - extra(origin.x - location.x, *width),
+                origin.y - extra(origin.y - location.y, *height),
+            );
+            let bottom_right = Point::new(
+                right + extra(location.x - right, *width),
+                bottom + extra(location.y - bottom, *height),
+            );
+
+            let grown_width = (bottom_right.x - top_left.x + 1) as usize;
+            let grown_height = (bottom_right.y - top_left.y + 1) as usize;
+            let mut grown = (0..grown_width * grown_height)
+                .map(|_| None)
+                .collect::<Vec<Option<T>>>();
+            for (ix, cell) in cells.drain(..).enumerate() {
+                if cell.is_some() {
+                    let location = Point::new(
+                        origin.x + (ix % *width) as i32,
+                        origin.y + (ix / *width) as i32,
+                    );
+                    let grown_ix = Self::index(&top_left, grown_width, grown_height, &location)
+                        .expect("Grown grid covers the old one");
+                    grown[grown_ix] = cell;
+                }
+            }
+
+            *origin = top_left;
+            *width = grown_width;
+            *height = grown_height;
+            *cells = grown;
+        }
+    }
+
+    pub fn get(&self, location: &Point) -> Option<&T> {
+        match &self.storage {
+            Storage::Sparse(cells) => cells.get(location),
+            Storage::Dense {
+                origin,
+                width,
+                height,
+                cells,
+            } => Self::index(origin, *width, *height, location).and_then(|ix| cells[ix].as_ref()),
+        }
+    }
+
+    pub fn get_mut(&mut self, location: &Point) -> Option<&mut T> {
+        match &mut self.storage {
+            Storage::Sparse(cells) => cells.get_mut(location),
+            Storage::Dense {
+                origin,
+                width,
+                height,
+                cells,
+            } => Self::index(origin, *width, *height, location)
+                .and_then(move |ix| cells[ix].as_mut()),
+        }
+    }
+
+    pub fn contains(&self, location: &Point) -> bool {
+        self.get(location).is_some()
+    }
+
+    /// Sets a cell, returns the previous value if there was one
+    pub fn insert(&mut self, location: Point, value: T) -> Option<T> {
+        self.extends = Some(match self.extends.take() {
+            Some((min, max)) => (
+                Point::new(min.x.min(location.x), min.y.min(location.y)),
+                Point::new(max.x.max(location.x), max.y.max(location.y)),
+            ),
+            None => (location, location),
+        });
+
+        self.grow(&location);
+
+        let previous = match &mut self.storage {
+            Storage::Sparse(cells) => cells.insert(location, value),
+            Storage::Dense {
+                origin,
+                width,
+                height,
+                cells,
+            } => {
+                let ix =
+                    Self::index(origin, *width, *height, &location).expect("Grid did not grow");
+                cells[ix].replace(value)
+            }
+        };
+
+        if previous.is_none() {
+            self.len += 1;
+        }
+        previous
+    }
+
+    /// Number of cells that are set
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the top left and bottom right corner of all cells set so far
+    pub fn extends(&self) -> Option<(Point, Point)> {
+        self.extends
+    }
+
+    /// All cells that are set, in no particular order
+    pub fn iter(&self) -> Box<dyn Iterator<Item = (Point, &T)> + '_> {
+        match &self.storage {
+            Storage::Sparse(cells) => Box::new(cells.iter().map(|(l, v)| (*l, v))),
+            Storage::Dense {
+                origin,
+                width,
+                cells,
+                ..
+            } => Box::new(cells.iter().enumerate().filter_map(move |(ix, cell)| {
+                cell.as_ref().map(|v| {
+                    (
+                        Point::new(
+                            origin.x + (ix % width) as i32,
+                            origin.y + (ix / width) as i32,
+                        ),
+                        v,
+                    )
+                })
+            })),
+        }
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.iter().map(|(_, v)| v)
+    }
+
+    /// Locations around a location, whether they are set or not
+    pub fn adjacent(location: &Point, adjacency: Adjacency) -> impl Iterator<Item = Point> {
+        let location = *location;
+        adjacency
+            .offsets()
+            .iter()
+            .map(move |(dx, dy)| location + Point::new(*dx, *dy))
+    }
+
+    /// Cells around a location that are set
+    pub fn neighbors<'a>(
+        &'a self,
+        location: &Point,
+        adjacency: Adjacency,
+    ) -> impl Iterator<Item = (Point, &'a T)> + 'a {
+        Self::adjacent(location, adjacency).filter_map(move |l| self.get(&l).map(|v| (l, v)))
+    }
+
+    /// Parses text where each line is a row (y increasing downwards) and each character a cell.
+    /// Characters for which cell returns None are left unset
+    pub fn parse<F>(input: &str, cell: F) -> Grid<T>
+    where
+        F: Fn(char) -> Option<T>,
+    {
+        let mut grid = Grid::new();
+        for (y, line) in input.lines().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                if let Some(value) = cell(c) {
+                    grid.insert(Point::new(x as i32, y as i32), value);
+                }
+            }
+        }
+        grid
+    }
+
+    /// Renders the cells from top left to bottom right as text, one line per row
+    pub fn render_area<F>(&self, top_left: &Point, bottom_right: &Point, cell: F) -> String
+    where
+        F: Fn(&Point, Option<&T>) -> char,
+    {
+        let mut result = String::new();
+        for y in top_left.y..=bottom_right.y {
+            for x in top_left.x..=bottom_right.x {
+                let location = Point::new(x, y);
+                result.push(cell(&location, self.get(&location)));
+            }
+            result.push('\n');
+        }
+        result
+    }
+
+    /// Renders all cells set so far as text, one line per row
+    pub fn render<F>(&self, cell: F) -> String
+    where
+        F: Fn(&Point, Option<&T>) -> char,
+    {
+        match self.extends() {
+            Some((top_left, bottom_right)) => self.render_area(&top_left, &bottom_right, cell),
+            None => String::new(),
+        }
+    }
+}
+
+impl<T> FromIterator<(Point, T)> for Grid<T> {
+    fn from_iter<I: IntoIterator<Item = (Point, T)>>(iter: I) -> Self {
+        let mut grid = Grid::new();
+        for (location, value) in iter {
+            grid.insert(location, value);
+        }
+        grid
+    }
+}
+
+impl<T> IntoIterator for Grid<T> {
+    type Item = (Point, T);
+    type IntoIter = std::vec::IntoIter<(Point, T)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let cells: Vec<(Point, T)> = match self.storage {
+            Storage::Sparse(cells) => cells.into_iter().collect(),
+            Storage::Dense {
+                origin,
+                width,
+                cells,
+                ..
+            } => cells
+                .into_iter()
+                .enumerate()
+                .filter_map(|(ix, cell)| {
+                    cell.map(|v| {
+                        (
+                            Point::new(
+                                origin.x + (ix % width) as i32,
+                                origin.y + (ix / width) as i32,
+                            ),
+                            v,
+                        )
+                    })
+                })
+                .collect(),
+        };
+        cells.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(grid: Grid<char>) -> Grid<char> {
+        let mut grid = grid;
+        for (y, line) in ["#.#", "..#"].iter().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                grid.insert(Point::new(x as i32, y as i32), c);
+            }
+        }
+        grid
+    }
+
+    #[test]
+    fn sparse_and_dense_behave_the_same() {
+        let sparse = fill(Grid::new());
+        let dense = fill(Grid::dense(&Point::new(0, 0), &Point::new(0, 0)));
+
+        for grid in [sparse, dense].iter() {
+            assert_eq!(grid.len(), 6);
+            assert_eq!(grid.extends(), Some((Point::new(0, 0), Point::new(2, 1))));
+            assert_eq!(grid.get(&Point::new(2, 1)), Some(&'#'));
+            assert_eq!(grid.get(&Point::new(3, 1)), None);
+            assert_eq!(grid.render(|_, c| *c.unwrap()), "#.#\n..#\n");
+            assert_eq!(
+                grid.neighbors(&Point::new(1, 0), Adjacency::Four)
+                    .filter(|(_, c)| **c == '#')
+                    .count(),
+                2
+            );
+            assert_eq!(
+                grid.neighbors(&Point::new(1, 0), Adjacency::Eight).count(),
+                5
+            );
+            assert_eq!(grid.iter().filter(|(_, c)| **c == '#').count(), 3);
+        }
+    }
+
+    #[test]
+    fn dense_grows_in_all_directions() {
+        let mut grid = Grid::dense(&Point::new(0, 0), &Point::new(1, 1));
+        grid.insert(Point::new(1, 1), 1);
+        grid.insert(Point::new(-2, 3), 2);
+        grid.insert(Point::new(4, -1), 3);
+        assert_eq!(grid.insert(Point::new(1, 1), 4), Some(1));
+
+        assert_eq!(grid.len(), 3);
+        assert_eq!(grid.get(&Point::new(1, 1)), Some(&4));
+        assert_eq!(grid.get(&Point::new(-2, 3)), Some(&2));
+        assert_eq!(grid.get(&Point::new(4, -1)), Some(&3));
+        assert_eq!(grid.extends(), Some((Point::new(-2, -1), Point::new(4, 3))));
+    }
+
+    #[test]
+    fn dense_grows_with_room_to_spare() {
+        let size = |grid: &Grid<i32>| match &grid.storage {
+            Storage::Dense { width, height, .. } => (*width, *height),
+            Storage::Sparse(_) => unreachable!(),
+        };
+
+        // Filled one column to the right and one row up at a time
+        let mut grid = Grid::dense(&Point::new(0, 0), &Point::new(0, 0));
+        let mut reallocations = 0;
+        for n in 1..=1000 {
+            let before = size(&grid);
+            grid.insert(Point::new(n, 0), n);
+            grid.insert(Point::new(0, -n), -n);
+            if size(&grid) != before {
+                reallocations += 1;
+            }
+        }
+
+        assert!(reallocations <= 20, "{} reallocations", reallocations);
+        assert_eq!(grid.len(), 2000);
+        assert_eq!(
+            grid.extends(),
+            Some((Point::new(0, -1000), Point::new(1000, 0)))
+        );
+        assert!((1..=1000).all(|n| grid.get(&Point::new(n, 0)) == Some(&n)));
+        assert!((1..=1000).all(|n| grid.get(&Point::new(0, -n)) == Some(&-n)));
+        assert_eq!(grid.get(&Point::new(0, 0)), None);
+        assert_eq!(grid.get(&Point::new(1, -1)), None);
+        assert_eq!(grid.iter().count(), 2000);
+    }
+
+    #[test]
+    fn parses_and_renders_text() {
+        let grid = Grid::parse("..#\n#..\n", |c| if c == '#' { Some(true) } else { None });
+
+        assert_eq!(grid.len(), 2);
+        assert!(grid.contains(&Point::new(2, 0)));
+        assert_eq!(
+            grid.render_area(&Point::new(0, 0), &Point::new(2, 1), |_, c| {
+                if c.is_some() {
+                    '#'
+                } else {
+                    ' '
+                }
+            }),
+            "  #\n#  \n"
+        );
+    }
+}
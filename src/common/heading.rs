@@ -0,0 +1,199 @@
+use std::fmt;
+
+use super::Point;
+
+/// Direction in which the y axis of a map increases
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum YAxis {
+    /// North has a larger y, like a plot
+    Up,
+    /// North has a smaller y, like lines of text on a screen
+    Down,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Turn {
+    Left,
+    Right,
+    Around,
+}
+
+/// One of the four directions on a map
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Heading {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Heading {
+    /// All headings, clockwise starting north
+    pub const ALL: [Heading; 4] = [Heading::North, Heading::East, Heading::South, Heading::West];
+
+    pub fn turn(self, turn: Turn) -> Heading {
+        match turn {
+            Turn::Left => self.turn_left(),
+            Turn::Right => self.turn_right(),
+            Turn::Around => self.turn_around(),
+        }
+    }
+
+    pub fn turn_left(self) -> Heading {
+        match self {
+            Heading::North => Heading::West,
+            Heading::West => Heading::South,
+            Heading::South => Heading::East,
+            Heading::East => Heading::North,
+        }
+    }
+
+    pub fn turn_right(self) -> Heading {
+        match self {
+            Heading::North => Heading::East,
+            Heading::East => Heading::South,
+            Heading::South => Heading::West,
+            Heading::West => Heading::North,
+        }
+    }
+
+    pub fn turn_around(self) -> Heading {
+        self.turn_left().turn_left()
+    }
+
+    /// Change in location for a single step
+    pub fn offset(self, y_axis: YAxis) -> Point {
+        let north = match y_axis {
+            YAxis::Up => 1,
+            YAxis::Down => -1,
+        };
+
+        match self {
+            Heading::North => Point::new(0, north),
+            Heading::East => Point::new(1, 0),
+            Heading::South => Point::new(0, -north),
+            Heading::West => Point::new(-1, 0),
+        }
+    }
+
+    /// Location one step from location in this heading
+    pub fn step(self, location: &Point, y_axis: YAxis) -> Point {
+        *location + self.offset(y_axis)
+    }
+
+    /// Parses the arrows '^', '>', 'v' and '<'
+    pub fn from_arrow(c: char) -> Option<Heading> {
+        match c {
+            '^' => Some(Heading::North),
+            '>' => Some(Heading::East),
+            'v' => Some(Heading::South),
+            '<' => Some(Heading::West),
+            _ => None,
+        }
+    }
+
+    pub fn arrow(self) -> char {
+        match self {
+            Heading::North => '^',
+            Heading::East => '>',
+            Heading::South => 'v',
+            Heading::West => '<',
+        }
+    }
+
+    /// Parses the compass points 'N', 'E', 'S' and 'W'
+    pub fn from_compass(c: char) -> Option<Heading> {
+        match c {
+            'N' => Some(Heading::North),
+            'E' => Some(Heading::East),
+            'S' => Some(Heading::South),
+            'W' => Some(Heading::West),
+            _ => None,
+        }
+    }
+
+    pub fn compass(self) -> char {
+        match self {
+            Heading::North => 'N',
+            Heading::East => 'E',
+            Heading::South => 'S',
+            Heading::West => 'W',
+        }
+    }
+
+    /// Movement command of the repair droid (day 15): north 1, south 2, west 3 and east 4
+    pub fn droid_command(self) -> i64 {
+        match self {
+            Heading::North => 1,
+            Heading::South => 2,
+            Heading::West => 3,
+            Heading::East => 4,
+        }
+    }
+
+    pub fn from_droid_command(command: i64) -> Option<Heading> {
+        match command {
+            1 => Some(Heading::North),
+            2 => Some(Heading::South),
+            3 => Some(Heading::West),
+            4 => Some(Heading::East),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Heading {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.arrow())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turns() {
+        for heading in Heading::ALL.iter().cloned() {
+            assert_eq!(heading.turn_left().turn_right(), heading);
+            assert_eq!(heading.turn(Turn::Around).turn(Turn::Around), heading);
+            assert_eq!(heading.turn_around(), heading.turn_right().turn_right());
+        }
+        assert_eq!(Heading::North.turn(Turn::Right), Heading::East);
+        assert_eq!(Heading::North.turn(Turn::Left), Heading::West);
+    }
+
+    #[test]
+    fn steps_follow_the_y_axis() {
+        let origin = Point::new(0, 0);
+        assert_eq!(Heading::North.step(&origin, YAxis::Up), Point::new(0, 1));
+        assert_eq!(Heading::North.step(&origin, YAxis::Down), Point::new(0, -1));
+        assert_eq!(Heading::West.step(&origin, YAxis::Down), Point::new(-1, 0));
+
+        // Turning right is a clockwise rotation as seen on the map
+        for heading in Heading::ALL.iter().cloned() {
+            assert_eq!(
+                heading.turn_right().offset(YAxis::Up),
+                heading.offset(YAxis::Up).rotate_right()
+            );
+            assert_eq!(
+                heading.turn_right().offset(YAxis::Down),
+                heading.offset(YAxis::Down).rotate_left()
+            );
+        }
+    }
+
+    #[test]
+    fn encodings_round_trip() {
+        for heading in Heading::ALL.iter().cloned() {
+            assert_eq!(Heading::from_arrow(heading.arrow()), Some(heading));
+            assert_eq!(Heading::from_compass(heading.compass()), Some(heading));
+            assert_eq!(
+                Heading::from_droid_command(heading.droid_command()),
+                Some(heading)
+            );
+        }
+        assert_eq!(Heading::from_arrow('#'), None);
+        assert_eq!(Heading::from_droid_command(0), None);
+    }
+}
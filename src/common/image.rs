@@ -0,0 +1,410 @@
+//! Export of visualizations as images
+//!
+//! The ImageRenderer collects the text frames of a visualization and writes them as a sequence of PNG files or as an
+//! animated GIF once the visualization is finished. Every character of a frame becomes a square cell of pixels with
+//! the color the palette gives that character. Frames of different size are drawn at the top left of an image that
+//! fits the largest frame. The images are configured by the environment:
+//!     ADVENT_CELL_SIZE  size of a cell in pixels (default 4)
+//!     ADVENT_PALETTE    colors of characters, e.g. "#=ffffff,.=202020,o=ff4040", on top of the default palette
+//!     ADVENT_FPS        frame rate of an animated GIF (default 30)
+
+use std::borrow::Cow;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+
+use super::render::Renderer;
+
+pub const CELL_SIZE_VAR: &str = "ADVENT_CELL_SIZE";
+pub const PALETTE_VAR: &str = "ADVENT_PALETTE";
+pub const FPS_VAR: &str = "ADVENT_FPS";
+
+pub type Rgb = [u8; 3];
+
+/// Colors of the characters in frames, characters without a color are magenta so they stand out
+#[derive(Clone, Debug, PartialEq)]
+pub struct Palette {
+    /// The first color is the background, used for spaces and for the area outside smaller frames
+    colors: Vec<(char, Rgb)>,
+}
+
+const UNKNOWN: Rgb = [255, 0, 255];
+
+impl Default for Palette {
+    /// Colors for the characters used by the visualizations of the puzzles
+    fn default() -> Palette {
+        Palette {
+            colors: vec![
+                (' ', [0, 0, 0]),
+                ('.', [32, 32, 32]),
+                ('#', [255, 255, 255]),
+                ('*', [255, 160, 0]),
+                ('+', [128, 128, 128]),
+                ('o', [255, 64, 64]),
+                ('_', [64, 200, 64]),
+                ('^', [255, 255, 0]),
+                ('>', [255, 255, 0]),
+                ('v', [255, 255, 0]),
+                ('<', [255, 255, 0]),
+            ],
+        }
+    }
+}
+
+impl Palette {
+    /// Sets the color of a character, replacing its previous color
+    pub fn set(&mut self, c: char, color: Rgb) {
+        match self.colors.iter_mut().find(|(k, _)| *k == c) {
+            Some(entry) => entry.1 = color,
+            None => self.colors.push((c, color)),
+        }
+    }
+
+    /// Applies a specification like "#=ffffff,.=202020" to the palette
+    pub fn apply(&mut self, spec: &str) -> Result<(), String> {
+        for entry in spec.split(',').filter(|e| !e.is_empty()) {
+            let mut chars = entry.chars();
+            let (c, rest) = match (chars.next(), chars.next()) {
+                (Some(c), Some('=')) => (c, chars.as_str()),
+                _ => return Err(format!("Expected <character>=<rrggbb>, got {:?}", entry)),
+            };
+            let color = u32::from_str_radix(rest, 16)
+                .ok()
+                .filter(|_| rest.len() == 6)
+                .ok_or_else(|| format!("Invalid color {:?} for {:?}", rest, c))?;
+
+            self.set(c, [(color >> 16) as u8, (color >> 8) as u8, color as u8]);
+        }
+
+        if self.colors.len() >= 256 {
+            return Err("A palette has at most 255 colors".to_string());
+        }
+        Ok(())
+    }
+
+    /// Index of the color of a character
+    fn index(&self, c: char) -> u8 {
+        self.colors
+            .iter()
+            .position(|(k, _)| *k == c)
+            .unwrap_or(self.colors.len()) as u8
+    }
+
+    /// Colors by index as consecutive red, green and blue bytes, ending with the color for unknown characters
+    fn rgb(&self) -> Vec<u8> {
+        self.colors
+            .iter()
+            .map(|(_, color)| color)
+            .chain(std::iter::once(&UNKNOWN))
+            .flat_map(|color| color.iter().cloned())
+            .collect()
+    }
+}
+
+/// Indexed image, one byte per pixel
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+/// Size of a frame in cells
+fn frame_size(frame: &str) -> (usize, usize) {
+    let lines = frame.trim_end_matches('\n').lines();
+    let width = lines.clone().map(|l| l.chars().count()).max().unwrap_or(0);
+    (width, lines.count())
+}
+
+/// Draws a frame at the top left of an image of given size in cells
+pub fn rasterize(frame: &str, size: (usize, usize), palette: &Palette, cell_size: usize) -> Image {
+    let width = size.0 * cell_size;
+    let height = size.1 * cell_size;
+    let mut pixels = vec![0; width * height];
+
+    for (row, line) in frame
+        .trim_end_matches('\n')
+        .lines()
+        .take(size.1)
+        .enumerate()
+    {
+        for (column, c) in line.chars().take(size.0).enumerate() {
+            let index = palette.index(c);
+            for y in row * cell_size..(row + 1) * cell_size {
+                let start = y * width + column * cell_size;
+                pixels[start..start + cell_size]
+                    .iter_mut()
+                    .for_each(|p| *p = index);
+            }
+        }
+    }
+
+    Image {
+        width,
+        height,
+        pixels,
+    }
+}
+
+fn encoding_error<E: std::error::Error + Send + Sync + 'static>(error: E) -> io::Error {
+    io::Error::other(error)
+}
+
+pub fn write_png(path: &Path, image: &Image, palette: &Palette) -> io::Result<()> {
+    let mut encoder = png::Encoder::new(
+        BufWriter::new(File::create(path)?),
+        image.width as u32,
+        image.height as u32,
+    );
+    encoder.set_color(png::ColorType::Indexed);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_palette(palette.rgb());
+
+    let mut writer = encoder.write_header().map_err(encoding_error)?;
+    writer
+        .write_image_data(&image.pixels)
+        .map_err(encoding_error)
+}
+
+/// Writes an animated GIF that loops forever
+pub fn write_gif(path: &Path, images: &[Image], palette: &Palette, fps: u32) -> io::Result<()> {
+    let (width, height) = images.first().map_or((0, 0), |i| (i.width, i.height));
+    if width > u16::MAX as usize || height > u16::MAX as usize {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{}x{} is too large for a GIF", width, height),
+        ));
+    }
+
+    let mut encoder = gif::Encoder::new(
+        BufWriter::new(File::create(path)?),
+        width as u16,
+        height as u16,
+        &palette.rgb(),
+    )
+    .map_err(encoding_error)?;
+    encoder
+        .set_repeat(gif::Repeat::Infinite)
+        .map_err(encoding_error)?;
+
+    // GIF delays are in hundredths of a second
+    let delay = 100u32.checked_div(fps).map_or(0, |d| d.max(1) as u16);
+    for image in images {
+        let frame = gif::Frame {
+            width: image.width as u16,
+            height: image.height as u16,
+            delay,
+            buffer: Cow::Borrowed(&image.pixels),
+            ..gif::Frame::default()
+        };
+        encoder.write_frame(&frame).map_err(encoding_error)?;
+    }
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ImageFormat {
+    /// Numbered PNG files in a directory
+    Png(PathBuf),
+    /// Animated GIF file
+    Gif(PathBuf),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageOptions {
+    pub cell_size: usize,
+    pub palette: Palette,
+    pub fps: u32,
+}
+
+impl Default for ImageOptions {
+    fn default() -> ImageOptions {
+        ImageOptions {
+            cell_size: 4,
+            palette: Palette::default(),
+            fps: 30,
+        }
+    }
+}
+
+impl ImageOptions {
+    /// Options configured by the environment
+    pub fn from_env() -> ImageOptions {
+        let mut options = ImageOptions::default();
+        let var = |name| env::var(name).ok().filter(|v: &String| !v.is_empty());
+
+        if let Some(size) = var(CELL_SIZE_VAR) {
+            options.cell_size = size
+                .parse()
+                .ok()
+                .filter(|s| *s > 0)
+                .unwrap_or_else(|| panic!("{} must be a positive number", CELL_SIZE_VAR));
+        }
+        if let Some(spec) = var(PALETTE_VAR) {
+            options
+                .palette
+                .apply(&spec)
+                .unwrap_or_else(|e| panic!("{}: {}", PALETTE_VAR, e));
+        }
+        if let Some(fps) = var(FPS_VAR) {
+            options.fps = fps
+                .parse()
+                .unwrap_or_else(|_| panic!("{} must be a number", FPS_VAR));
+        }
+        options
+    }
+}
+
+/// Collects frames and writes them as images when finished
+pub struct ImageRenderer {
+    format: ImageFormat,
+    options: ImageOptions,
+    frames: Vec<String>,
+}
+
+impl ImageRenderer {
+    pub fn new(format: ImageFormat, options: ImageOptions) -> ImageRenderer {
+        ImageRenderer {
+            format,
+            options,
+            frames: Vec::new(),
+        }
+    }
+
+    /// Writes the frames collected so far, returns the files written
+    pub fn write(&self) -> io::Result<Vec<PathBuf>> {
+        let size = self
+            .frames
+            .iter()
+            .map(|f| frame_size(f))
+            .fold((0, 0), |a, b| (a.0.max(b.0), a.1.max(b.1)));
+        let images = self
+            .frames
+            .iter()
+            .map(|frame| rasterize(frame, size, &self.options.palette, self.options.cell_size));
+
+        match &self.format {
+            ImageFormat::Png(dir) => {
+                fs::create_dir_all(dir)?;
+                images
+                    .enumerate()
+                    .map(|(ix, image)| {
+                        let path = dir.join(format!("frame_{:05}.png", ix + 1));
+                        write_png(&path, &image, &self.options.palette).map(|_| path)
+                    })
+                    .collect()
+            }
+            ImageFormat::Gif(path) => {
+                let images = images.collect::<Vec<Image>>();
+                write_gif(path, &images, &self.options.palette, self.options.fps)?;
+                Ok(vec![path.clone()])
+            }
+        }
+    }
+}
+
+impl Renderer for ImageRenderer {
+    fn frame(&mut self, frame: &str) {
+        self.frames.push(frame.to_string());
+    }
+
+    fn finish(&mut self) {
+        if self.frames.is_empty() {
+            return;
+        }
+
+        let files = self
+            .write()
+            .unwrap_or_else(|e| panic!("Could not write images: {}", e));
+        println!(
+            "Wrote {} frames to {}",
+            self.frames.len(),
+            match &self.format {
+                ImageFormat::Png(dir) => format!("{} PNG files in {}", files.len(), dir.display()),
+                ImageFormat::Gif(path) => path.display().to_string(),
+            }
+        );
+        self.frames.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn palettes() {
+        let mut palette = Palette::default();
+        palette.apply("#=102030,x=ffffff").unwrap();
+
+        assert_eq!(palette.index(' '), 0);
+        assert_eq!(palette.index('#'), 2);
+        assert_eq!(&palette.rgb()[6..9], &[0x10, 0x20, 0x30]);
+        assert_eq!(palette.index('x') as usize, palette.colors.len() - 1);
+        // Unknown characters get the last color
+        assert_eq!(palette.index('?') as usize, palette.colors.len());
+        assert_eq!(&palette.rgb()[palette.rgb().len() - 3..], &UNKNOWN);
+
+        assert!(palette.apply("#ffffff").is_err());
+        assert!(palette.apply("#=fff").is_err());
+        assert!(palette.apply("#=gggggg").is_err());
+    }
+
+    #[test]
+    fn frames_become_cells() {
+        let palette = Palette::default();
+        let image = rasterize("#.\n #\n\n", frame_size("#.\n #\n\n"), &palette, 2);
+
+        assert_eq!((image.width, image.height), (4, 4));
+        let (white, dark) = (palette.index('#'), palette.index('.'));
+        assert_eq!(
+            image.pixels,
+            vec![
+                white, white, dark, dark, white, white, dark, dark, 0, 0, white, white, 0, 0,
+                white, white
+            ]
+        );
+
+        // Smaller frames are padded with the background
+        let image = rasterize("#", (2, 1), &palette, 1);
+        assert_eq!(image.pixels, vec![white, 0]);
+    }
+
+    #[test]
+    fn writes_png_and_gif() {
+        let dir = env::temp_dir().join(format!("advent-images-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let mut png = ImageRenderer::new(
+            ImageFormat::Png(dir.join("frames")),
+            ImageOptions::default(),
+        );
+        png.frame("#.");
+        png.frame("#.\n.#");
+        let files = png.write().unwrap();
+        assert_eq!(files.len(), 2);
+        assert!(files[1].ends_with("frame_00002.png"));
+        let decoder = png::Decoder::new(File::open(&files[0]).unwrap());
+        let reader = decoder.read_info().unwrap();
+        assert_eq!((reader.info().width, reader.info().height), (8, 8));
+
+        let mut gif = ImageRenderer::new(
+            ImageFormat::Gif(dir.join("run.gif")),
+            ImageOptions::default(),
+        );
+        gif.frame("o_");
+        gif.frame("_o");
+        let files = gif.write().unwrap();
+        let mut decoder = gif::DecodeOptions::new()
+            .read_info(File::open(&files[0]).unwrap())
+            .unwrap();
+        let mut frames = 0;
+        while decoder.read_next_frame().unwrap().is_some() {
+            frames += 1;
+        }
+        assert_eq!(frames, 2);
+        assert_eq!((decoder.width(), decoder.height()), (8, 4));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,186 @@
+//! Loading of puzzle inputs
+//!
+//! The input of day N is read from `dayN.txt` in the input directory. That is `inputs` relative to the working
+//! directory, unless the ADVENT_INPUTS environment variable names another directory. Inputs that are missing are
+//! downloaded into the input directory when a session token is configured, see fetch.
+
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use super::fetch::Fetcher;
+
+/// Environment variable that overrides the input directory
+pub const INPUT_DIR_VAR: &str = "ADVENT_INPUTS";
+
+pub const DEFAULT_INPUT_DIR: &str = "inputs";
+
+#[derive(Debug)]
+pub enum InputError {
+    /// The file could not be read
+    Io { path: PathBuf, error: io::Error },
+    /// A line of the file could not be parsed, line numbers start at 1
+    Parse {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+    /// The input could not be downloaded
+    Fetch { url: String, message: String },
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Io { path, error } => {
+                write!(f, "Could not read {}: {}", path.display(), error)
+            }
+            InputError::Parse {
+                path,
+                line,
+                message,
+            } => write!(f, "{}:{}: {}", path.display(), line, message),
+            InputError::Fetch { url, message } => write!(f, "Could not fetch {}: {}", url, message),
+        }
+    }
+}
+
+impl Error for InputError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            InputError::Io { error, .. } => Some(error),
+            InputError::Parse { .. } | InputError::Fetch { .. } => None,
+        }
+    }
+}
+
+/// Directory that holds the puzzle inputs
+pub fn input_dir() -> PathBuf {
+    env::var_os(INPUT_DIR_VAR)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_INPUT_DIR))
+}
+
+/// Location of the input of a day within the input directory
+pub fn input_path(day: u32) -> PathBuf {
+    input_dir().join(format!("day{}.txt", day))
+}
+
+pub fn read_file(path: &Path) -> Result<String, InputError> {
+    fs::read_to_string(path).map_err(|error| InputError::Io {
+        path: path.to_path_buf(),
+        error,
+    })
+}
+
+/// Parses every non-empty line of a file
+pub fn parse_lines<T>(path: &Path) -> Result<Vec<T>, InputError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    read_file(path)?
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(ix, line)| {
+            line.trim().parse::<T>().map_err(|e| InputError::Parse {
+                path: path.to_path_buf(),
+                line: ix + 1,
+                message: format!("{} ({:?})", e, line),
+            })
+        })
+        .collect()
+}
+
+/// Input of a day, without trailing whitespace. When a session token is configured a missing or empty input is
+/// downloaded first
+pub fn load(day: u32) -> Result<String, InputError> {
+    let path = match Fetcher::from_env() {
+        Some(fetcher) => fetcher.ensure(day)?,
+        None => input_path(day),
+    };
+
+    read_file(&path).map(|text| text.trim_end().to_string())
+}
+
+/// Input of a day parsed line by line
+pub fn load_lines<T>(day: u32) -> Result<Vec<T>, InputError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    match Fetcher::from_env() {
+        Some(fetcher) => parse_lines(&fetcher.ensure(day)?),
+        None => parse_lines(&input_path(day)),
+    }
+}
+
+/// Input of a day for the puzzle solutions, which have no way to recover from a missing input
+pub fn puzzle_input(day: u32) -> String {
+    load(day).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Input of a day parsed line by line for the puzzle solutions
+pub fn puzzle_lines<T>(day: u32) -> Vec<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    load_lines(day).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Text of any file, like test data or a program given on the command line
+pub fn import_lines(name: &str) -> String {
+    read_file(Path::new(name)).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// FNV-1a hash of an input. Unlike the std hashers it is the same on every platform and release
+pub fn input_hash(input: &str) -> String {
+    let hash = input.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_name_the_file() {
+        let missing = Path::new("inputs/no-such-day.txt");
+        let error = read_file(missing).unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("Could not read inputs/no-such-day.txt: "));
+
+        let path = env::temp_dir().join(format!("advent-input-{}.txt", std::process::id()));
+        fs::write(&path, "12\n\n14\nfourteen\n").unwrap();
+        let error = parse_lines::<i64>(&path).unwrap_err();
+        fs::remove_file(&path).unwrap();
+
+        match error {
+            InputError::Parse { line, .. } => assert_eq!(line, 4),
+            _ => panic!("Expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn hash_is_stable() {
+        assert_eq!(input_hash(""), "cbf29ce484222325");
+        assert_eq!(input_hash("a"), "af63dc4c8601ec8c");
+        assert_ne!(input_hash("12\n14"), input_hash("14\n12"));
+    }
+
+    #[test]
+    fn every_day_has_an_input() {
+        for day in 1..=17 {
+            assert!(input_path(day).exists(), "No input for day {}", day);
+        }
+    }
+}
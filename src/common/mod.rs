@@ -1,59 +1,119 @@
-use std::fs;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-
-#[derive(Debug,Clone, Hash)]
-pub struct Vector {
-    pub x: i32,
-    pub y: i32,
-    pub z: i32,
-}
+pub mod fetch;
 
-impl std::fmt::Display for Vector {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "({},{},{})", self.x, self.y, self.z)
+mod grid;
+pub use grid::*;
+
+mod heading;
+pub use heading::*;
+
+pub mod image;
+
+mod input;
+pub use input::*;
+
+mod point;
+pub use point::*;
+
+mod render;
+pub use render::*;
+
+/// Location on a two dimensional map
+pub type Point = Point2<i32>;
+
+impl Point {
+    pub fn path(&self, other: &Point) -> Path {
+        let offset = *other - *self;
+
+        Path {
+            target: *other,
+            direction: Direction::new(&offset).expect("No path to the same location"),
+            distance: offset.manhattan_length(),
+        }
     }
 }
 
-impl std::cmp::PartialEq for Vector {
-    fn eq(&self, other: &Vector) -> bool {
-        self.x == other.x && self.y == other.y && self.z == other.z
+fn gcd(a: i32, b: i32) -> i32 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
     }
 }
 
-impl std::cmp::Eq for Vector {}
+/// Exact direction of an offset on a map with the y axis pointing down: the offset divided by the greatest
+/// common divisor of its components, so all offsets along the same line of sight have the same direction.
+/// Directions are ordered clockwise, starting with up
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Direction {
+    dx: i32,
+    dy: i32,
+}
 
-impl Vector {
-    pub fn new(x: i32, y: i32, z: i32) -> Vector {
-        Vector { x: x, y: y, z: z }
+impl Direction {
+    /// Direction of an offset, None for a zero offset
+    pub fn new(offset: &Point) -> Option<Direction> {
+        let divisor = gcd(offset.x, offset.y);
+        if divisor == 0 {
+            None
+        } else {
+            Some(Direction {
+                dx: offset.x / divisor,
+                dy: offset.y / divisor,
+            })
+        }
     }
 
-    pub fn path(&self, other: &Vector) -> Path {
-        let x = (other.x - self.x) as f32;
-        let y = (other.y - self.y) as f32;
+    /// Smallest offset with this direction
+    pub fn offset(&self) -> Point {
+        Point::new(self.dx, self.dy)
+    }
 
-        Path {
-            target: other.clone(),
-            angle: angle_in_degrees(y.atan2(x)),
-            distance: x.hypot(y),
+    /// Right half (up included) comes before the left half (down included)
+    fn half(&self) -> u8 {
+        if self.dx > 0 || (self.dx == 0 && self.dy < 0) {
+            0
+        } else {
+            1
         }
     }
 
-    pub fn magnitude(&self) -> i32 {
-        self.x.abs() + self.y.abs() + self.z.abs()
+    /// Angle in degrees, 0 is up and increases clockwise. Only meant for display, it is not exact
+    pub fn degrees(&self) -> f32 {
+        angle_in_degrees((self.dy as f32).atan2(self.dx as f32))
+    }
+}
+
+impl Ord for Direction {
+    fn cmp(&self, other: &Direction) -> std::cmp::Ordering {
+        // Within a half the cross product is positive when other is further clockwise
+        let cross = self.dx as i64 * other.dy as i64 - self.dy as i64 * other.dx as i64;
+        self.half().cmp(&other.half()).then_with(|| 0.cmp(&cross))
+    }
+}
+
+impl PartialOrd for Direction {
+    fn partial_cmp(&self, other: &Direction) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::fmt::Display for Direction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:.2}", self.degrees())
     }
 }
 
 pub struct Path {
-    pub target: Vector,
-    pub angle: f32,
-    pub distance: f32,
+    pub target: Point,
+    pub direction: Direction,
+    /// Manhattan distance to the target
+    pub distance: i32,
 }
 
-// Path is considered equal when they have the same angle
+// Path is considered equal when they have the same direction
 impl std::cmp::PartialEq for Path {
     fn eq(&self, other: &Path) -> bool {
-        return self.angle == other.angle;
+        self.direction == other.direction
     }
 }
 
@@ -66,7 +126,11 @@ impl PartialOrd for Path {
 
 impl std::fmt::Display for Path {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "T:{} A:{},D:{}", self.target, self.angle, self.distance)
+        write!(
+            f,
+            "T:{} A:{},D:{}",
+            self.target, self.direction, self.distance
+        )
     }
 }
 
@@ -85,16 +149,39 @@ fn angle_in_degrees(angle: f32) -> f32 {
     (r + 90f32) % 360f32
 }
 
-pub fn import(name: &str) -> Vec<i64> {
-    let file = File::open(name).unwrap();
-    let reader = BufReader::new(file);
-    let mut vec = Vec::new();
-    for (_, line) in reader.lines().enumerate() {
-        vec.push(line.unwrap().parse::<i64>().unwrap())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn directions_are_exact_and_clockwise() {
+        let origin = Point::new(0, 0);
+
+        // Offsets that differ by less than f32 precision still have different directions
+        assert_ne!(
+            origin.path(&Point::new(100_000, 99_999)).direction,
+            origin.path(&Point::new(99_999, 99_998)).direction
+        );
+        assert!(origin.path(&Point::new(2, -4)) == origin.path(&Point::new(1, -2)));
+
+        let clockwise = [
+            (0, -1),
+            (1, -3),
+            (1, -1),
+            (1, 0),
+            (2, 1),
+            (0, 1),
+            (-1, 1),
+            (-1, 0),
+            (-1, -5),
+        ]
+        .iter()
+        .map(|(x, y)| Direction::new(&Point::new(*x, *y)).unwrap())
+        .collect::<Vec<_>>();
+        let mut sorted = clockwise.clone();
+        sorted.reverse();
+        sorted.sort();
+        assert_eq!(sorted, clockwise);
+        assert_eq!(Direction::new(&origin), None);
     }
-    return vec;
-}
-
-pub fn import_lines(name: &str) -> String {
-    fs::read_to_string(name).expect("Error parsing file")
 }
@@ -0,0 +1,229 @@
+use std::fmt;
+use std::hash::Hash;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// Integer types that can be used as coordinates
+pub trait Coordinate:
+    Copy
+    + Default
+    + Ord
+    + Hash
+    + fmt::Debug
+    + fmt::Display
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+{
+    fn abs(self) -> Self;
+    fn to_f64(self) -> f64;
+}
+
+macro_rules! coordinate {
+    ($($t:ty),*) => ($(
+        impl Coordinate for $t {
+            fn abs(self) -> Self {
+                <$t>::abs(self)
+            }
+
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+        }
+    )*);
+}
+
+coordinate!(i8, i16, i32, i64, i128, isize);
+
+/// Point or vector in two dimensions. Ordered by x, then y
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point2<T> {
+    pub x: T,
+    pub y: T,
+}
+
+/// Point or vector in three dimensions. Ordered by x, then y, then z
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T: Coordinate> Point2<T> {
+    pub fn new(x: T, y: T) -> Point2<T> {
+        Point2 { x, y }
+    }
+
+    /// Manhattan distance to the origin
+    pub fn manhattan_length(&self) -> T {
+        self.x.abs() + self.y.abs()
+    }
+
+    pub fn manhattan(&self, other: &Point2<T>) -> T {
+        (*other - *self).manhattan_length()
+    }
+
+    /// Largest distance along any axis, the number of king moves between points
+    pub fn chebyshev(&self, other: &Point2<T>) -> T {
+        let d = *other - *self;
+        std::cmp::max(d.x.abs(), d.y.abs())
+    }
+
+    pub fn euclidean(&self, other: &Point2<T>) -> f64 {
+        let d = *other - *self;
+        d.x.to_f64().hypot(d.y.to_f64())
+    }
+
+    /// Rotates 90 degrees counterclockwise around the origin, with the y axis pointing up.
+    /// When the y axis points down (screen coordinates) this is a clockwise rotation
+    pub fn rotate_left(&self) -> Point2<T> {
+        Point2::new(-self.y, self.x)
+    }
+
+    /// Rotates 90 degrees clockwise around the origin, with the y axis pointing up.
+    /// When the y axis points down (screen coordinates) this is a counterclockwise rotation
+    pub fn rotate_right(&self) -> Point2<T> {
+        Point2::new(self.y, -self.x)
+    }
+}
+
+impl<T: Coordinate> Point3<T> {
+    pub fn new(x: T, y: T, z: T) -> Point3<T> {
+        Point3 { x, y, z }
+    }
+
+    /// Manhattan distance to the origin
+    pub fn manhattan_length(&self) -> T {
+        self.x.abs() + self.y.abs() + self.z.abs()
+    }
+
+    pub fn manhattan(&self, other: &Point3<T>) -> T {
+        (*other - *self).manhattan_length()
+    }
+
+    /// Largest distance along any axis
+    pub fn chebyshev(&self, other: &Point3<T>) -> T {
+        let d = *other - *self;
+        std::cmp::max(std::cmp::max(d.x.abs(), d.y.abs()), d.z.abs())
+    }
+
+    pub fn euclidean(&self, other: &Point3<T>) -> f64 {
+        let d = *other - *self;
+        (d.x.to_f64().powi(2) + d.y.to_f64().powi(2) + d.z.to_f64().powi(2)).sqrt()
+    }
+}
+
+macro_rules! operators {
+    ($point:ident, $($field:ident),*) => {
+        impl<T: Coordinate> Add for $point<T> {
+            type Output = $point<T>;
+
+            fn add(self, other: $point<T>) -> $point<T> {
+                $point { $($field: self.$field + other.$field),* }
+            }
+        }
+
+        impl<T: Coordinate> Sub for $point<T> {
+            type Output = $point<T>;
+
+            fn sub(self, other: $point<T>) -> $point<T> {
+                $point { $($field: self.$field - other.$field),* }
+            }
+        }
+
+        /// Scaling by a factor
+        impl<T: Coordinate> Mul<T> for $point<T> {
+            type Output = $point<T>;
+
+            fn mul(self, factor: T) -> $point<T> {
+                $point { $($field: self.$field * factor),* }
+            }
+        }
+
+        impl<T: Coordinate> Neg for $point<T> {
+            type Output = $point<T>;
+
+            fn neg(self) -> $point<T> {
+                $point { $($field: -self.$field),* }
+            }
+        }
+
+        impl<T: Coordinate> AddAssign for $point<T> {
+            fn add_assign(&mut self, other: $point<T>) {
+                $(self.$field += other.$field;)*
+            }
+        }
+
+        impl<T: Coordinate> SubAssign for $point<T> {
+            fn sub_assign(&mut self, other: $point<T>) {
+                $(self.$field -= other.$field;)*
+            }
+        }
+    };
+}
+
+operators!(Point2, x, y);
+operators!(Point3, x, y, z);
+
+impl<T: fmt::Display> fmt::Display for Point2<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({},{})", self.x, self.y)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Point3<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({},{},{})", self.x, self.y, self.z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic() {
+        let a = Point2::new(3, -4);
+        let b = Point2::new(1, 2);
+
+        assert_eq!(a + b, Point2::new(4, -2));
+        assert_eq!(a - b, Point2::new(2, -6));
+        assert_eq!(a * 2, Point2::new(6, -8));
+        assert_eq!(-a, Point2::new(-3, 4));
+
+        let mut c = Point3::new(1i64, 2, 3);
+        c += Point3::new(1, 1, 1);
+        c -= Point3::new(0, 0, 5);
+        assert_eq!(c, Point3::new(2, 3, -1));
+    }
+
+    #[test]
+    fn distances() {
+        let a = Point2::new(1, 1);
+        let b = Point2::new(4, 5);
+
+        assert_eq!(a.manhattan(&b), 7);
+        assert_eq!(a.chebyshev(&b), 4);
+        assert_eq!(a.euclidean(&b), 5.0);
+        assert_eq!(Point3::new(-1, 2, -3).manhattan_length(), 6);
+    }
+
+    #[test]
+    fn rotation_and_order() {
+        let up = Point2::new(0, 1);
+        assert_eq!(up.rotate_right(), Point2::new(1, 0));
+        assert_eq!(up.rotate_left(), Point2::new(-1, 0));
+        assert_eq!(up.rotate_left().rotate_left(), -up);
+        assert_eq!(up.rotate_right().rotate_left(), up);
+
+        let mut points = vec![Point2::new(1, 0), Point2::new(0, 2), Point2::new(0, 1)];
+        points.sort();
+        assert_eq!(
+            points,
+            vec![Point2::new(0, 1), Point2::new(0, 2), Point2::new(1, 0)]
+        );
+    }
+}
@@ -0,0 +1,261 @@
+//! Visualization of solutions
+//!
+//! Solutions that can show their progress draw frames, complete pictures of their state as text with one line per
+//! row, on a Renderer. By default that is the NullRenderer so solutions run at full speed, the ADVENT_RENDER
+//! environment variable selects another:
+//!     none        no visualization
+//!     plain       frames one after another as plain text, e.g. to write to a file
+//!     ansi[:fps]  frames in place on an ANSI terminal, at most fps frames per second (default 30, 0 is unlimited)
+//!     png:<dir>   frames as numbered PNG files in a directory, see image for the options
+//!     gif:<file>  frames as an animated GIF
+
+use std::env;
+use std::fmt;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::image::{ImageFormat, ImageOptions, ImageRenderer};
+
+/// Environment variable that selects the renderer
+pub const RENDER_VAR: &str = "ADVENT_RENDER";
+
+pub trait Renderer {
+    /// Whether frames are shown at all, when they are not there is no need to draw them
+    fn enabled(&self) -> bool {
+        true
+    }
+
+    /// Shows a frame, one line of text per row
+    fn frame(&mut self, frame: &str);
+
+    /// Called after the last frame, renderers that collect frames write them now
+    fn finish(&mut self) {}
+}
+
+impl dyn Renderer + '_ {
+    /// Shows the frame drawn by draw, which is only called when the renderer is enabled
+    pub fn draw<F: FnOnce() -> String>(&mut self, draw: F) {
+        if self.enabled() {
+            self.frame(&draw());
+        }
+    }
+}
+
+/// Renderer that shows nothing
+pub struct NullRenderer;
+
+impl Renderer for NullRenderer {
+    fn enabled(&self) -> bool {
+        false
+    }
+
+    fn frame(&mut self, _frame: &str) {}
+}
+
+/// Writes frames one after another, each preceded by a line with its number
+pub struct PlainRenderer<W: Write> {
+    out: W,
+    frames: usize,
+}
+
+impl<W: Write> PlainRenderer<W> {
+    pub fn new(out: W) -> PlainRenderer<W> {
+        PlainRenderer { out, frames: 0 }
+    }
+}
+
+impl<W: Write> Renderer for PlainRenderer<W> {
+    fn frame(&mut self, frame: &str) {
+        self.frames += 1;
+        writeln!(self.out, "--- frame {} ---", self.frames)
+            .and_then(|_| writeln!(self.out, "{}", frame.trim_end_matches('\n')))
+            .expect("Could not write frame");
+    }
+}
+
+/// Draws frames in place on an ANSI terminal, limited to a frame rate
+pub struct AnsiRenderer<W: Write> {
+    out: W,
+    interval: Duration,
+    last: Option<Instant>,
+}
+
+impl<W: Write> AnsiRenderer<W> {
+    /// Renderer that shows at most fps frames per second, 0 is unlimited
+    pub fn new(out: W, fps: u32) -> AnsiRenderer<W> {
+        AnsiRenderer {
+            out,
+            interval: if fps == 0 {
+                Duration::from_secs(0)
+            } else {
+                Duration::from_secs(1) / fps
+            },
+            last: None,
+        }
+    }
+}
+
+impl<W: Write> Renderer for AnsiRenderer<W> {
+    fn frame(&mut self, frame: &str) {
+        match self.last {
+            // Clear the screen before the first frame
+            None => write!(self.out, "\x1B[2J").expect("Could not write frame"),
+            Some(last) => {
+                let elapsed = last.elapsed();
+                if elapsed < self.interval {
+                    thread::sleep(self.interval - elapsed);
+                }
+            }
+        }
+        self.last = Some(Instant::now());
+
+        // Move the cursor to the top left and draw over the previous frame
+        write!(self.out, "\x1B[1;1H{}", frame)
+            .and_then(|_| self.out.flush())
+            .expect("Could not write frame");
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RenderMode {
+    None,
+    Plain,
+    Ansi { fps: u32 },
+    Png { dir: PathBuf },
+    Gif { file: PathBuf },
+}
+
+impl FromStr for RenderMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<RenderMode, String> {
+        let mut parts = s.splitn(2, ':');
+        match (parts.next(), parts.next()) {
+            (Some("none"), None) => Ok(RenderMode::None),
+            (Some("plain"), None) => Ok(RenderMode::Plain),
+            (Some("ansi"), None) => Ok(RenderMode::Ansi { fps: 30 }),
+            (Some("ansi"), Some(fps)) => fps
+                .parse()
+                .map(|fps| RenderMode::Ansi { fps })
+                .map_err(|_| format!("Invalid frame rate {:?}", fps)),
+            (Some("png"), Some(dir)) if !dir.is_empty() => Ok(RenderMode::Png { dir: dir.into() }),
+            (Some("gif"), Some(file)) if !file.is_empty() => Ok(RenderMode::Gif { file: file.into() }),
+            _ => Err(format!(
+                "Unknown render mode {:?}, expected none, plain, ansi[:fps], png:<dir> or gif:<file>",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for RenderMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenderMode::None => write!(f, "none"),
+            RenderMode::Plain => write!(f, "plain"),
+            RenderMode::Ansi { fps } => write!(f, "ansi:{}", fps),
+            RenderMode::Png { dir } => write!(f, "png:{}", dir.display()),
+            RenderMode::Gif { file } => write!(f, "gif:{}", file.display()),
+        }
+    }
+}
+
+impl RenderMode {
+    /// Mode selected by the environment, none when it is not set
+    pub fn from_env() -> RenderMode {
+        env::var(RENDER_VAR)
+            .ok()
+            .filter(|mode| !mode.is_empty())
+            .map_or(RenderMode::None, |mode| {
+                mode.parse()
+                    .unwrap_or_else(|e| panic!("{}: {}", RENDER_VAR, e))
+            })
+    }
+
+    /// Renderer that shows frames on stdout or writes them to files in this mode
+    pub fn renderer(self) -> Box<dyn Renderer> {
+        match self {
+            RenderMode::None => Box::new(NullRenderer),
+            RenderMode::Plain => Box::new(PlainRenderer::new(io::stdout())),
+            RenderMode::Ansi { fps } => Box::new(AnsiRenderer::new(io::stdout(), fps)),
+            RenderMode::Png { dir } => Box::new(ImageRenderer::new(
+                ImageFormat::Png(dir),
+                ImageOptions::from_env(),
+            )),
+            RenderMode::Gif { file } => Box::new(ImageRenderer::new(
+                ImageFormat::Gif(file),
+                ImageOptions::from_env(),
+            )),
+        }
+    }
+}
+
+/// Renderer selected by the environment, for visualizing puzzle solutions
+pub fn renderer() -> Box<dyn Renderer> {
+    RenderMode::from_env().renderer()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modes() {
+        assert_eq!("none".parse(), Ok(RenderMode::None));
+        assert_eq!("plain".parse(), Ok(RenderMode::Plain));
+        assert_eq!("ansi".parse(), Ok(RenderMode::Ansi { fps: 30 }));
+        assert_eq!("ansi:0".parse(), Ok(RenderMode::Ansi { fps: 0 }));
+        assert!("ansi:fast".parse::<RenderMode>().is_err());
+        assert_eq!(
+            "gif:out/hull.gif".parse(),
+            Ok(RenderMode::Gif {
+                file: "out/hull.gif".into()
+            })
+        );
+        assert!("gif".parse::<RenderMode>().is_err());
+        assert!("png:".parse::<RenderMode>().is_err());
+
+        for mode in [
+            RenderMode::None,
+            RenderMode::Plain,
+            RenderMode::Ansi { fps: 5 },
+            RenderMode::Png {
+                dir: "frames".into(),
+            },
+        ]
+        .iter()
+        {
+            assert_eq!(mode.to_string().parse().as_ref(), Ok(mode));
+        }
+    }
+
+    #[test]
+    fn frames() {
+        let mut drawn = 0;
+        let renderer: &mut dyn Renderer = &mut NullRenderer;
+        renderer.draw(|| {
+            drawn += 1;
+            String::new()
+        });
+        assert_eq!(drawn, 0);
+
+        let mut plain = PlainRenderer::new(Vec::new());
+        (&mut plain as &mut dyn Renderer).draw(|| "#.\n.#\n".to_string());
+        plain.frame("##");
+        assert_eq!(
+            String::from_utf8(plain.out).unwrap(),
+            "--- frame 1 ---\n#.\n.#\n--- frame 2 ---\n##\n"
+        );
+
+        let mut ansi = AnsiRenderer::new(Vec::new(), 0);
+        ansi.frame("a");
+        ansi.frame("b");
+        assert_eq!(
+            String::from_utf8(ansi.out).unwrap(),
+            "\x1B[2J\x1B[1;1Ha\x1B[1;1Hb"
+        );
+    }
+}
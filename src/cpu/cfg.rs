@@ -0,0 +1,294 @@
+//! Static control flow analysis of Intcode programs
+//!
+//! Starting at address 0 every reachable instruction is decoded, jump targets in IMMEDIATE mode are
+//! followed and the code is split into basic blocks. Jumps with a target that is only known at runtime
+//! (POSITION or RELATIVE mode) can not be resolved and are flagged as computed.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use super::*;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum EdgeKind {
+    /// Execution continues with the next instruction
+    Fallthrough,
+    /// A jump to a statically known target
+    Jump,
+}
+
+#[derive(Clone, Debug)]
+pub struct Edge {
+    pub target: usize,
+    pub kind: EdgeKind,
+}
+
+pub struct BasicBlock {
+    pub start: usize,
+    /// Addresses of the instructions in this block
+    pub instructions: Vec<usize>,
+    pub successors: Vec<Edge>,
+    /// The block ends in a jump whose target is only known at runtime
+    pub computed: bool,
+    /// Execution runs into memory that does not decode as an instruction
+    pub invalid: bool,
+}
+
+impl BasicBlock {
+    fn new(start: usize) -> BasicBlock {
+        BasicBlock {
+            start,
+            instructions: vec![],
+            successors: vec![],
+            computed: false,
+            invalid: false,
+        }
+    }
+}
+
+pub struct ControlFlowGraph {
+    pub blocks: BTreeMap<usize, BasicBlock>,
+}
+
+/// Where execution can go after the given instruction.
+/// Returns (fall through to next instruction, static jump target, computed jump)
+fn flow(instruction: &Instruction) -> (bool, Option<usize>, bool) {
+    match instruction.opcode {
+        Opcode::QUIT => (false, None, false),
+        Opcode::JIT | Opcode::JIZ => {
+            let condition = &instruction.parameters[0];
+            let target = &instruction.parameters[1];
+
+            // An IMMEDIATE condition decides the jump statically, e.g. 1105,1,x is an unconditional jump
+            let (may_fall, may_jump) = if condition.mode == ParameterMode::IMMEDIATE {
+                let taken = (condition.value != 0) == (instruction.opcode == Opcode::JIT);
+                (!taken, taken)
+            } else {
+                (true, true)
+            };
+
+            if !may_jump {
+                (may_fall, None, false)
+            } else if target.mode == ParameterMode::IMMEDIATE && target.value >= 0 {
+                (may_fall, Some(target.value as usize), false)
+            } else {
+                (may_fall, None, true)
+            }
+        }
+        _ => (true, None, false),
+    }
+}
+
+impl ControlFlowGraph {
+    pub fn build(program: &Program) -> ControlFlowGraph {
+        // First pass, find all reachable instructions and the block leaders
+        let mut leaders = BTreeSet::new();
+        let mut reachable = BTreeMap::<usize, Instruction>::new();
+        let mut invalid = BTreeSet::new();
+        let mut work = vec![0];
+        leaders.insert(0);
+
+        while let Some(address) = work.pop() {
+            if reachable.contains_key(&address) || invalid.contains(&address) {
+                continue;
+            }
+
+            let instruction = match Instruction::decode(program, address) {
+                Some(instruction) => instruction,
+                None => {
+                    invalid.insert(address);
+                    continue;
+                }
+            };
+
+            let (falls, target, _) = flow(&instruction);
+            let jumps = instruction.opcode == Opcode::JIT || instruction.opcode == Opcode::JIZ;
+
+            if falls {
+                let next = address + instruction.size;
+                // The instruction after a jump starts a new block
+                if jumps {
+                    leaders.insert(next);
+                }
+                work.push(next);
+            }
+            if let Some(target) = target {
+                leaders.insert(target);
+                work.push(target);
+            }
+
+            reachable.insert(address, instruction);
+        }
+
+        // Second pass, walk from each leader until the block ends
+        let mut blocks = BTreeMap::new();
+        for &leader in &leaders {
+            let mut block = BasicBlock::new(leader);
+            let mut address = leader;
+
+            loop {
+                let instruction = match reachable.get(&address) {
+                    Some(instruction) => instruction,
+                    None => {
+                        block.invalid = true;
+                        break;
+                    }
+                };
+                block.instructions.push(address);
+
+                let (falls, target, computed) = flow(instruction);
+                // QUIT has a size that would overflow, only look at the next address when execution falls through
+                let next = if falls {
+                    address + instruction.size
+                } else {
+                    address
+                };
+                let jumps = instruction.opcode == Opcode::JIT || instruction.opcode == Opcode::JIZ;
+
+                if jumps || !falls {
+                    if let Some(target) = target {
+                        block.successors.push(Edge {
+                            target,
+                            kind: EdgeKind::Jump,
+                        });
+                    }
+                    if falls {
+                        block.successors.push(Edge {
+                            target: next,
+                            kind: EdgeKind::Fallthrough,
+                        });
+                    }
+                    block.computed = computed;
+                    break;
+                }
+
+                if leaders.contains(&next) {
+                    block.successors.push(Edge {
+                        target: next,
+                        kind: EdgeKind::Fallthrough,
+                    });
+                    break;
+                }
+                address = next;
+            }
+
+            blocks.insert(leader, block);
+        }
+
+        ControlFlowGraph { blocks }
+    }
+
+    /// Returns the graph in Graphviz DOT format, one node per basic block listing its disassembly
+    pub fn to_dot(&self, program: &Program) -> String {
+        let mut dot = String::new();
+
+        writeln!(dot, "digraph intcode {{").unwrap();
+        writeln!(dot, "    node [shape=box fontname=monospace];").unwrap();
+
+        for block in self.blocks.values() {
+            let mut label = String::new();
+            for &address in &block.instructions {
+                let instruction = Instruction::parse_at(program, address);
+                write!(
+                    label,
+                    "{:5}: {}\\l",
+                    address,
+                    instruction.to_string().replace('\t', " ")
+                )
+                .unwrap();
+            }
+            if block.invalid {
+                label.push_str("<invalid>\\l");
+            }
+
+            let style = if block.invalid {
+                " color=red"
+            } else if block.computed {
+                " color=orange"
+            } else {
+                ""
+            };
+            writeln!(dot, "    b{} [label=\"{}\"{}];", block.start, label, style).unwrap();
+
+            for edge in &block.successors {
+                let style = match edge.kind {
+                    EdgeKind::Jump => "",
+                    EdgeKind::Fallthrough => " [style=dashed]",
+                };
+                writeln!(dot, "    b{} -> b{}{};", block.start, edge.target, style).unwrap();
+            }
+
+            if block.computed {
+                writeln!(dot, "    b{} -> computed [color=orange];", block.start).unwrap();
+            }
+        }
+
+        if self.blocks.values().any(|b| b.computed) {
+            writeln!(
+                dot,
+                "    computed [label=\"computed target\" shape=ellipse color=orange];"
+            )
+            .unwrap();
+        }
+
+        writeln!(dot, "}}").unwrap();
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_blocks_at_jumps_and_targets() {
+        // 0: IN [12], 2: JIZ [12] -> 9, 5: OUT 1, 7: QUIT, 8: pad, 9: OUT 0, 11: QUIT
+        let program = read("3,12,1006,12,9,104,1,99,0,104,0,99,0");
+        let cfg = ControlFlowGraph::build(&program);
+
+        assert_eq!(
+            cfg.blocks.keys().cloned().collect::<Vec<_>>(),
+            vec![0, 5, 9]
+        );
+        assert_eq!(cfg.blocks[&0].instructions, vec![0, 2]);
+
+        let successors = &cfg.blocks[&0].successors;
+        assert_eq!(successors.len(), 2);
+        assert_eq!(
+            (successors[0].target, successors[0].kind),
+            (9, EdgeKind::Jump)
+        );
+        assert_eq!(
+            (successors[1].target, successors[1].kind),
+            (5, EdgeKind::Fallthrough)
+        );
+        assert!(cfg.blocks[&5].successors.is_empty());
+    }
+
+    #[test]
+    fn unconditional_jump_and_computed_target() {
+        // 0: JIT 1 -> 6, 3: QUIT (unreachable), 6: JIZ 0 -> [rb-1]
+        let program = read("1105,1,6,99,0,0,2106,0,-1");
+        let cfg = ControlFlowGraph::build(&program);
+
+        assert_eq!(cfg.blocks.keys().cloned().collect::<Vec<_>>(), vec![0, 6]);
+        assert_eq!(cfg.blocks[&0].successors.len(), 1);
+        assert!(cfg.blocks[&6].computed);
+        assert!(cfg.blocks[&6].successors.is_empty());
+
+        let dot = cfg.to_dot(&program);
+        assert!(dot.starts_with("digraph intcode {"));
+        assert!(dot.contains("b0 -> b6;"));
+        assert!(dot.contains("b6 -> computed"));
+    }
+
+    #[test]
+    fn flags_invalid_code() {
+        // 0: JIT 1 -> 4, 3: QUIT, 4: 42 is not an opcode
+        let program = read("1105,1,4,99,42");
+        let cfg = ControlFlowGraph::build(&program);
+
+        assert!(cfg.blocks[&4].invalid);
+        assert!(cfg.blocks[&4].instructions.is_empty());
+    }
+}
@@ -0,0 +1,299 @@
+//! Property based tests for the Intcode VM
+//!
+//! Random but well formed programs are generated and executed one instruction at a time, checking the
+//! invariants of the VM after every step. Malformed instructions are checked to fail only with the errors
+//! documented by `Parameter::parse` and `Instruction::parse`.
+
+use std::panic;
+
+use super::*;
+
+/// Small xorshift generator, good enough to produce reproducible random programs
+struct Random {
+    state: u64,
+}
+
+impl Random {
+    fn new(seed: u64) -> Random {
+        Random {
+            state: seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1,
+        }
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// Random value in [0, n)
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    /// Random value in [low, high]
+    fn between(&mut self, low: CpuWidth, high: CpuWidth) -> CpuWidth {
+        low + (self.next() % (high - low + 1) as u64) as CpuWidth
+    }
+}
+
+/// Memory layout of generated programs:
+/// code starts at 0, followed by read-only constants and a scratch area that is only written to.
+/// Addresses from FAR onwards are never written, reading them must yield 0.
+const CODE_SIZE: usize = 200;
+const CONSTANTS: usize = CODE_SIZE;
+const CONSTANTS_SIZE: usize = 16;
+const SCRATCH: usize = CONSTANTS + CONSTANTS_SIZE;
+const SCRATCH_SIZE: usize = 16;
+const FAR: usize = 10_000;
+
+const MAX_STEPS: usize = 500;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Source {
+    /// Operand that may feed arithmetic, never reads the scratch area so values can not grow unbounded
+    Arithmetic,
+    /// Operand that may read anything
+    Any,
+}
+
+/// Encodes a read operand and returns its (mode, value)
+fn read_operand(rng: &mut Random, source: Source) -> (CpuWidth, CpuWidth) {
+    match rng.below(if source == Source::Any { 5 } else { 4 }) {
+        0 => (1, rng.between(-100, 100)),
+        1 => (0, (CONSTANTS + rng.below(CONSTANTS_SIZE)) as CpuWidth),
+        2 => (0, (FAR + rng.below(1000)) as CpuWidth),
+        // Relative base points at the constants
+        3 => (2, rng.below(CONSTANTS_SIZE) as CpuWidth),
+        _ => (0, (SCRATCH + rng.below(SCRATCH_SIZE)) as CpuWidth),
+    }
+}
+
+/// Encodes a write operand and returns its (mode, value)
+fn write_operand(rng: &mut Random) -> (CpuWidth, CpuWidth) {
+    let offset = rng.below(SCRATCH_SIZE);
+    if rng.below(2) == 0 {
+        (0, (SCRATCH + offset) as CpuWidth)
+    } else {
+        (2, (SCRATCH - CONSTANTS + offset) as CpuWidth)
+    }
+}
+
+fn encode(opcode: CpuWidth, operands: &[(CpuWidth, CpuWidth)]) -> Vec<CpuWidth> {
+    let modes = operands
+        .iter()
+        .enumerate()
+        .fold(0, |acc, (rank, (mode, _))| {
+            acc + mode * [100, 1000, 10000][rank]
+        });
+
+    let mut result = vec![opcode + modes];
+    result.extend(operands.iter().map(|(_, value)| *value));
+    result
+}
+
+/// Generates a random well formed program, the first instruction points the relative base at the constants
+fn generate(rng: &mut Random) -> Vec<CpuWidth> {
+    // Decide the opcodes first so jump targets can point at instruction boundaries
+    let mut opcodes = vec![9];
+    let mut starts = vec![0];
+    let mut size = 2;
+    loop {
+        let opcode = [1, 2, 3, 4, 5, 6, 7, 8][rng.below(8)];
+        let width = match opcode {
+            1 | 2 | 7 | 8 => 4,
+            3 | 4 => 2,
+            _ => 3,
+        };
+
+        // Always leave room for the final QUIT
+        if size + width >= CODE_SIZE {
+            break;
+        }
+        opcodes.push(opcode);
+        starts.push(size);
+        size += width;
+    }
+    starts.push(size);
+
+    let mut memory = vec![];
+    for opcode in opcodes {
+        let instruction = match opcode {
+            9 => encode(9, &[(1, CONSTANTS as CpuWidth)]),
+            1 | 2 => encode(
+                opcode,
+                &[
+                    read_operand(rng, Source::Arithmetic),
+                    read_operand(rng, Source::Arithmetic),
+                    write_operand(rng),
+                ],
+            ),
+            7 | 8 => encode(
+                opcode,
+                &[
+                    read_operand(rng, Source::Any),
+                    read_operand(rng, Source::Any),
+                    write_operand(rng),
+                ],
+            ),
+            3 => encode(opcode, &[write_operand(rng)]),
+            4 => encode(opcode, &[read_operand(rng, Source::Any)]),
+            _ => encode(
+                opcode,
+                &[
+                    read_operand(rng, Source::Any),
+                    // Never jump back to the relative base setup
+                    (1, starts[1 + rng.below(starts.len() - 1)] as CpuWidth),
+                ],
+            ),
+        };
+        memory.extend(instruction);
+    }
+    memory.push(99);
+    memory.resize(CONSTANTS, 0);
+
+    for _ in 0..CONSTANTS_SIZE {
+        memory.push(rng.between(-100, 100));
+    }
+
+    memory
+}
+
+fn load(memory: &[CpuWidth], rng: &mut Random) -> Program {
+    let mut program = Program::new(memory.iter().cloned().enumerate().collect());
+
+    // Enough input that IN never falls back to stdin
+    for _ in 0..MAX_STEPS {
+        program.add_input(rng.between(-100, 100));
+    }
+    program
+}
+
+/// Executes a single instruction, returns false when the program quit
+fn step(program: &mut Program) -> bool {
+    let instruction = Instruction::parse(program);
+    if instruction.is_quit() {
+        program.state = 1;
+        return false;
+    }
+
+    let pc = program.pc;
+    let size = instruction.size;
+
+    // Determine where a jump would go before executing it
+    let jump = match instruction.opcode {
+        Opcode::JIT | Opcode::JIZ => {
+            let mut condition = Parameter::new();
+            condition.parse(instruction.source, 0, program.get(pc + 1));
+            let mut target = Parameter::new();
+            target.parse(instruction.source, 1, program.get(pc + 2));
+
+            let condition = condition.get(program).unwrap();
+            let taken = (instruction.opcode == Opcode::JIT) == (condition != 0);
+            if taken {
+                Some(target.get(program).unwrap() as usize)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    };
+
+    instruction.execute(program);
+
+    match jump {
+        Some(target) => assert_eq!(program.pc, target, "jump from {}", pc),
+        None => assert_eq!(program.pc, pc + size, "instruction at {}", pc),
+    }
+    true
+}
+
+fn snapshot(program: &Program) -> Vec<CpuWidth> {
+    (0..SCRATCH + SCRATCH_SIZE)
+        .chain(FAR..FAR + 1000)
+        .map(|address| program.get(address))
+        .collect()
+}
+
+#[test]
+fn generated_programs_keep_invariants() {
+    for seed in 0..300 {
+        let mut rng = Random::new(seed);
+        let memory = generate(&mut rng);
+        let mut program = load(&memory, &mut rng);
+
+        let fork_at = rng.below(MAX_STEPS);
+        let mut fork: Option<Program> = None;
+
+        for steps in 0..MAX_STEPS {
+            if steps == fork_at {
+                fork = Some(program.clone());
+            }
+
+            if let Some(fork) = fork.as_mut() {
+                if !fork.is_finished() {
+                    step(fork);
+                }
+            }
+
+            if !step(&mut program) {
+                break;
+            }
+        }
+
+        // Nothing ever writes outside of the scratch area
+        for address in FAR..FAR + 1000 {
+            assert_eq!(program.get(address), 0, "seed {} address {}", seed, address);
+        }
+        assert_eq!(&memory[..SCRATCH], &snapshot(&program)[..SCRATCH]);
+
+        // A clone taken halfway evolves exactly like the original
+        if let Some(fork) = fork {
+            assert_eq!(fork.pc, program.pc, "seed {}", seed);
+            assert_eq!(fork.output, program.output, "seed {}", seed);
+            assert_eq!(snapshot(&fork), snapshot(&program), "seed {}", seed);
+        }
+    }
+}
+
+#[test]
+fn malformed_instructions_fail_with_documented_errors() {
+    let documented = [
+        "Unknown opcode",
+        "Invalid parameter mode",
+        "must write in POSITION or RELATIVE mode",
+    ];
+
+    let mut rng = Random::new(2019);
+    let mut failures = vec![];
+    for _ in 0..2000 {
+        let instruction = rng.between(0, 39999);
+        let source = format!("{},4,5,6,99,0,0", instruction);
+
+        let result = panic::catch_unwind(|| {
+            let mut program = read(&source);
+            program.add_input(0);
+            let instruction = Instruction::parse(&program);
+            if !instruction.is_quit() {
+                instruction.execute(&mut program);
+            }
+        });
+
+        if let Err(error) = result {
+            let message = if let Some(message) = error.downcast_ref::<String>() {
+                message.clone()
+            } else if let Some(message) = error.downcast_ref::<&str>() {
+                message.to_string()
+            } else {
+                String::new()
+            };
+
+            if !documented.iter().any(|d| message.contains(d)) {
+                failures.push((instruction, message));
+            }
+        }
+    }
+
+    assert!(failures.is_empty(), "Undocumented failures {:?}", failures);
+}
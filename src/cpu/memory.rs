@@ -0,0 +1,227 @@
+//! Memory inspection for Intcode programs
+//!
+//! Dumps ranges of memory as integers (with an ASCII column), as text or as decoded instructions,
+//! compares snapshots and lists memory used beyond the end of the loaded program.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::ops::Range;
+use std::str::FromStr;
+
+use super::*;
+
+/// Copy of all memory cells that were ever set, ordered by address
+pub type Snapshot = BTreeMap<usize, CpuWidth>;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Format {
+    /// Rows of integers followed by their ASCII representation
+    Integer,
+    /// Memory as text, non printable values are shown as '.'
+    Ascii,
+    /// Disassembly, memory that does not decode is shown as data
+    Instruction,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        match source {
+            "int" => Ok(Format::Integer),
+            "ascii" => Ok(Format::Ascii),
+            "asm" => Ok(Format::Instruction),
+            _ => Err(format!(
+                "Unknown memory format {}, expected int, ascii or asm",
+                source
+            )),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Change {
+    pub address: usize,
+    pub before: CpuWidth,
+    pub after: CpuWidth,
+}
+
+impl std::fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:6}: {} -> {}", self.address, self.before, self.after)
+    }
+}
+
+impl Program {
+    pub fn snapshot(&self) -> Snapshot {
+        self.memory.iter().map(|(a, v)| (*a, *v)).collect()
+    }
+
+    /// Number of memory cells the program was loaded with
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
+fn printable(value: CpuWidth) -> char {
+    if (32..127).contains(&value) {
+        value as u8 as char
+    } else {
+        '.'
+    }
+}
+
+const ROW: usize = 8;
+
+pub fn dump(program: &Program, range: Range<usize>, format: Format) -> String {
+    let mut result = String::new();
+
+    match format {
+        Format::Integer => {
+            for row in range.clone().step_by(ROW) {
+                let end = std::cmp::min(row + ROW, range.end);
+                let values = (row..end).map(|a| program.get(a)).collect::<Vec<_>>();
+
+                write!(result, "{:6}:", row).unwrap();
+                for value in &values {
+                    write!(result, " {:>8}", value).unwrap();
+                }
+                for _ in values.len()..ROW {
+                    result.push_str("         ");
+                }
+                writeln!(
+                    result,
+                    "  |{}|",
+                    values.iter().map(|v| printable(*v)).collect::<String>()
+                )
+                .unwrap();
+            }
+        }
+        Format::Ascii => {
+            for row in range.clone().step_by(ROW * 8) {
+                let end = std::cmp::min(row + ROW * 8, range.end);
+                writeln!(
+                    result,
+                    "{:6}: {}",
+                    row,
+                    (row..end)
+                        .map(|a| printable(program.get(a)))
+                        .collect::<String>()
+                )
+                .unwrap();
+            }
+        }
+        Format::Instruction => {
+            let mut address = range.start;
+            while address < range.end {
+                match Instruction::decode(program, address) {
+                    Some(instruction) => {
+                        let operands = if instruction.is_quit() {
+                            0
+                        } else {
+                            instruction.size - 1
+                        };
+
+                        write!(result, "{:6}: {}", address, instruction.opcode).unwrap();
+                        for parameter in &instruction.parameters[..operands] {
+                            write!(result, " {}", parameter).unwrap();
+                        }
+                        result.push('\n');
+
+                        address += operands + 1;
+                    }
+                    None => {
+                        writeln!(result, "{:6}: DATA\t{}", address, program.get(address)).unwrap();
+                        address += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    result
+}
+
+/// All cells that differ between two snapshots, cells missing from a snapshot are 0
+pub fn diff(before: &Snapshot, after: &Snapshot) -> Vec<Change> {
+    let mut addresses = before
+        .keys()
+        .chain(after.keys())
+        .cloned()
+        .collect::<Vec<_>>();
+    addresses.sort_unstable();
+    addresses.dedup();
+
+    addresses
+        .into_iter()
+        .filter_map(|address| {
+            let a = before.get(&address).cloned().unwrap_or(0);
+            let b = after.get(&address).cloned().unwrap_or(0);
+            if a != b {
+                Some(Change {
+                    address,
+                    before: a,
+                    after: b,
+                })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Non-zero cells beyond the memory the program was loaded with
+pub fn beyond_program(program: &Program) -> Vec<(usize, CpuWidth)> {
+    program
+        .snapshot()
+        .into_iter()
+        .filter(|(address, value)| *address >= program.size() && *value != 0)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dumps_integers_ascii_and_instructions() {
+        let program = read("72,105,33,10,99");
+
+        assert_eq!(
+            dump(&program, 0..5, Format::Integer),
+            "     0:       72      105       33       10       99                             |Hi!.c|\n"
+        );
+        assert_eq!(dump(&program, 0..3, Format::Ascii), "     0: Hi!\n");
+
+        let program = read("1101,1,2,3,42,99");
+        assert_eq!(
+            dump(&program, 0..6, Format::Instruction),
+            "     0: ADD\t P:[I     1] P:[I     2] P:[P     3]\n     4: DATA\t42\n     5: QUIT\n"
+        );
+    }
+
+    #[test]
+    fn diffs_snapshots_and_finds_memory_beyond_program() {
+        // ADD 3 + 4 -> [2], ADD 1 + 1 -> [100]
+        let mut program = read("1101,3,4,2,1101,1,1,100,99");
+        let before = program.snapshot();
+        execute(&mut program);
+
+        assert_eq!(
+            diff(&before, &program.snapshot()),
+            vec![
+                Change {
+                    address: 2,
+                    before: 4,
+                    after: 7
+                },
+                Change {
+                    address: 100,
+                    before: 0,
+                    after: 2
+                },
+            ]
+        );
+        assert_eq!(beyond_program(&program), vec![(100, 2)]);
+    }
+}
@@ -2,11 +2,17 @@ use std::collections::HashMap;
 use std::fmt;
 use std::io::{self, Read, Write};
 
+pub mod cfg;
+pub mod memory;
+pub mod session;
+pub mod smc;
+
 pub type CpuWidth = i64;
 
 #[derive(Clone)]
 pub struct Program {
     memory: HashMap<usize, CpuWidth>,
+    size: usize,
     pc: usize,
     input: Vec<CpuWidth>,
     pub output: Vec<CpuWidth>,
@@ -14,11 +20,15 @@ pub struct Program {
     pub interactive: bool,
     relative_base: usize,
     state: i32,
+    steps: u64,
+    self_modification: Option<smc::SelfModification>,
+    session: Option<session::Session>,
 }
 
 impl Program {
     pub fn new(instructions: HashMap<usize, CpuWidth>) -> Program {
         Program {
+            size: instructions.len(),
             memory: instructions,
             pc: 0,
             relative_base: 0,
@@ -27,6 +37,9 @@ impl Program {
             input_ix: 0,
             interactive: false,
             state: 0,
+            steps: 0,
+            self_modification: None,
+            session: None,
         }
     }
 
@@ -40,9 +53,73 @@ impl Program {
     }
 
     pub fn set(&mut self, index: usize, value: CpuWidth) {
+        self.record_event(session::Event::Set {
+            step: self.steps,
+            address: index,
+            value,
+        });
+        self.memory.insert(index, value);
+    }
+
+    /// Memory write by an instruction of the program itself
+    fn write(&mut self, index: usize, value: CpuWidth) {
+        if let Some(tracker) = self.self_modification.as_mut() {
+            tracker.write(self.pc, index, value);
+        }
         self.memory.insert(index, value);
     }
 
+    /// Tells the self modification tracker that the instruction of given size at pc is executed
+    fn track_execution(&mut self, size: usize) {
+        if self.self_modification.is_some() {
+            let cells = (self.pc..self.pc + size)
+                .map(|address| self.get(address))
+                .collect::<Vec<CpuWidth>>();
+            if let Some(tracker) = self.self_modification.as_mut() {
+                tracker.execute(self.pc, &cells);
+            }
+        }
+    }
+
+    /// Start recording writes into executed code and execution of written memory
+    pub fn detect_self_modification(&mut self) {
+        self.self_modification = Some(smc::SelfModification::new());
+    }
+
+    /// Start recording all input, output and memory set from outside, see session
+    pub fn record(&mut self) {
+        self.session = Some(session::Session::new(self));
+    }
+
+    /// Session recorded so far, None when recording is not enabled
+    pub fn session(&self) -> Option<&session::Session> {
+        self.session.as_ref()
+    }
+
+    fn record_event(&mut self, event: session::Event) {
+        if let Some(session) = self.session.as_mut() {
+            session.events.push(event);
+        }
+    }
+
+    /// Number of instructions executed so far
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// Address of the next instruction
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+
+    /// Self modifications recorded so far, empty when detection is not enabled
+    pub fn self_modifications(&self) -> &[smc::Modification] {
+        match &self.self_modification {
+            Some(tracker) => &tracker.modifications,
+            None => &[],
+        }
+    }
+
     pub fn add_input(&mut self, input: CpuWidth) {
         self.input.push(input);
     }
@@ -145,12 +222,12 @@ impl Parameter {
     pub fn set(&mut self, program: &mut Program, value: CpuWidth) {
         match self.mode {
             ParameterMode::POSITION => {
-                program.memory.insert(self.value as usize, value);
+                program.write(self.value as usize, value);
             }
             ParameterMode::RELATIVE => {
                 let index = self.relative_address(program);
 
-                program.memory.insert(index, value);
+                program.write(index, value);
             }
             _ => panic!("Invalid instruction, trying to set a DIRECT parameter"),
         }
@@ -226,6 +303,8 @@ impl Instruction {
     }
 
     pub fn execute(mut self, program: &mut Program) {
+        program.track_execution(self.size);
+
         let mut new_pc = program.pc + self.size;
 
         match self.opcode {
@@ -268,7 +347,12 @@ impl Instruction {
                     }
                 }
 
-                &mut self.parameters[0].set(program, program.input[program.input_ix]);
+                let value = program.input[program.input_ix];
+                program.record_event(session::Event::Input {
+                    step: program.steps,
+                    value,
+                });
+                self.parameters[0].set(program, value);
                 program.input_ix += 1;
             }
 
@@ -277,6 +361,10 @@ impl Instruction {
                     .get(program)
                     .expect("Error, no output set");
 
+                program.record_event(session::Event::Output {
+                    step: program.steps,
+                    value: output,
+                });
                 program.output.push(output);
 
                 if program.interactive {
@@ -340,7 +428,12 @@ impl Instruction {
     }
 
     pub fn parse(program: &Program) -> Instruction {
-        let instruction = program.memory[&program.pc];
+        Instruction::parse_at(program, program.pc)
+    }
+
+    /// Parses the instruction at the given address instead of at the program counter
+    pub fn parse_at(program: &Program, address: usize) -> Instruction {
+        let instruction = program.memory[&address];
 
         let mut result = Instruction::new(instruction);
         result.size = 1;
@@ -364,11 +457,7 @@ impl Instruction {
                 for rank in 0..3 {
                     let p = &mut result.parameters[rank];
 
-                    p.parse(
-                        instruction,
-                        rank,
-                        program.memory[&(program.pc + result.size)],
-                    );
+                    p.parse(instruction, rank, program.memory[&(address + result.size)]);
                     result.size += 1;
                 }
 
@@ -379,18 +468,20 @@ impl Instruction {
             Opcode::IN | Opcode::OUT | Opcode::RB => {
                 let p = &mut result.parameters[0];
 
-                p.parse(instruction, 0, program.memory[&(program.pc + result.size)]);
+                p.parse(instruction, 0, program.memory[&(address + result.size)]);
                 result.size += 1;
+
+                if result.opcode == Opcode::IN
+                    && result.parameters[0].mode == ParameterMode::IMMEDIATE
+                {
+                    panic!("Instruction parse failure ({}): IN must write in POSITION or RELATIVE mode", instruction);
+                }
             }
             Opcode::JIT | Opcode::JIZ => {
                 for rank in 0..2 {
                     let p = &mut result.parameters[rank];
 
-                    p.parse(
-                        instruction,
-                        rank,
-                        program.memory[&(program.pc + result.size)],
-                    );
+                    p.parse(instruction, rank, program.memory[&(address + result.size)]);
                     result.size += 1;
                 }
             }
@@ -404,18 +495,54 @@ impl Instruction {
 
         result
     }
+
+    /// Like parse_at, but returns None when the memory at address does not hold a valid instruction
+    /// (unknown opcode or parameter mode, writing in IMMEDIATE mode or operands missing from memory)
+    pub fn decode(program: &Program, address: usize) -> Option<Instruction> {
+        let instruction = *program.memory.get(&address)?;
+
+        // Number of operands and which operand, if any, is written to
+        let (operands, writes) = match instruction % 100 {
+            1 | 2 | 7 | 8 => (3, Some(2)),
+            3 => (1, Some(0)),
+            4 | 9 => (1, None),
+            5 | 6 => (2, None),
+            99 => (0, None),
+            _ => return None,
+        };
+
+        for rank in 0..operands {
+            let mode = (instruction / [100, 1000, 10000][rank]) % 10;
+            if mode > 2
+                || (writes == Some(rank) && mode == 1)
+                || !program.memory.contains_key(&(address + rank + 1))
+            {
+                return None;
+            }
+        }
+
+        Some(Instruction::parse_at(program, address))
+    }
 }
 
 pub fn execute(program: &mut Program) -> bool {
-    while program.state == 0 {
+    execute_until(program, u64::MAX)
+}
+
+/// Like execute, but also pauses once the program has executed the given number of instructions
+pub fn execute_until(program: &mut Program, steps: u64) -> bool {
+    while program.state == 0 && program.steps < steps {
         let instruction = Instruction::parse(&program);
         // If the program is non-interactive and needs input let it pause
         if instruction.is_quit() {
+            // QUIT has no operands, its size only marks the end of the program
+            program.track_execution(1);
             program.state = 1;
         } else if instruction.is_input() && program.is_waiting() {
             break;
         } else {
             instruction.execute(program);
+            program.steps += 1;
         }
     }
 
@@ -440,3 +567,276 @@ pub fn read(input: &str) -> Program {
             .collect(),
     )
 }
+
+#[cfg(test)]
+mod fuzz;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs the program until it quits or waits for input and returns all output produced
+    fn run(source: &str, input: &[CpuWidth]) -> Vec<CpuWidth> {
+        let mut program = read(source);
+        for i in input {
+            program.add_input(*i);
+        }
+        execute(&mut program);
+        program.output
+    }
+
+    #[test]
+    fn add_and_mul_position_mode() {
+        let mut program = read("1,9,10,3,2,3,11,0,99,30,40,50");
+        execute(&mut program);
+        assert!(program.is_finished());
+        assert_eq!(program.get(3), 70);
+        assert_eq!(program.get(0), 3500);
+    }
+
+    #[test]
+    fn add_and_mul_small_programs() {
+        let cases: [(&str, usize, CpuWidth); 4] = [
+            ("1,0,0,0,99", 0, 2),
+            ("2,3,0,3,99", 3, 6),
+            ("2,4,4,5,99,0", 5, 9801),
+            ("1,1,1,4,99,5,6,0,99", 0, 30),
+        ];
+
+        for (source, address, expected) in cases.iter() {
+            let mut program = read(source);
+            execute(&mut program);
+            assert_eq!(program.get(*address), *expected, "program {}", source);
+        }
+    }
+
+    #[test]
+    fn immediate_mode_and_negative_values() {
+        let mut program = read("1002,4,3,4,33");
+        execute(&mut program);
+        assert_eq!(program.get(4), 99);
+
+        let mut program = read("1101,100,-1,4,0");
+        execute(&mut program);
+        assert_eq!(program.get(4), 99);
+    }
+
+    #[test]
+    fn input_and_output() {
+        assert_eq!(run("3,0,4,0,99", &[42]), vec![42]);
+        assert_eq!(run("104,-7,99", &[]), vec![-7]);
+    }
+
+    #[test]
+    fn waits_for_input_when_not_interactive() {
+        let mut program = read("3,0,4,0,99");
+        assert!(execute(&mut program));
+        assert!(program.is_waiting());
+        assert!(!program.is_finished());
+
+        program.add_input(5);
+        assert!(!execute(&mut program));
+        assert!(program.is_finished());
+        assert_eq!(program.output, vec![5]);
+    }
+
+    #[test]
+    fn equals_and_less_than() {
+        let cases: [(&str, CpuWidth, CpuWidth); 8] = [
+            ("3,9,8,9,10,9,4,9,99,-1,8", 8, 1),
+            ("3,9,8,9,10,9,4,9,99,-1,8", 7, 0),
+            ("3,9,7,9,10,9,4,9,99,-1,8", 7, 1),
+            ("3,9,7,9,10,9,4,9,99,-1,8", 8, 0),
+            ("3,3,1108,-1,8,3,4,3,99", 8, 1),
+            ("3,3,1108,-1,8,3,4,3,99", 9, 0),
+            ("3,3,1107,-1,8,3,4,3,99", -3, 1),
+            ("3,3,1107,-1,8,3,4,3,99", 8, 0),
+        ];
+
+        for (source, input, expected) in cases.iter() {
+            assert_eq!(
+                run(source, &[*input]),
+                vec![*expected],
+                "program {} input {}",
+                source,
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn jumps() {
+        let cases = [
+            "3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9",
+            "3,3,1105,-1,9,1101,0,0,12,4,12,99,1",
+        ];
+
+        for source in cases.iter() {
+            assert_eq!(run(source, &[0]), vec![0], "program {}", source);
+            assert_eq!(run(source, &[-4]), vec![1], "program {}", source);
+        }
+    }
+
+    #[test]
+    fn compare_to_eight() {
+        let source = "3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99";
+
+        assert_eq!(run(source, &[7]), vec![999]);
+        assert_eq!(run(source, &[8]), vec![1000]);
+        assert_eq!(run(source, &[9]), vec![1001]);
+    }
+
+    #[test]
+    fn quine_uses_relative_base_and_memory_beyond_program() {
+        let source = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99";
+        let expected = read(source);
+
+        let output = run(source, &[]);
+        assert_eq!(output.len(), 16);
+        for (ix, value) in output.iter().enumerate() {
+            assert_eq!(*value, expected.get(ix));
+        }
+    }
+
+    #[test]
+    fn large_numbers() {
+        assert_eq!(
+            run("1102,34915192,34915192,7,4,7,99,0", &[]),
+            vec![1219070632396864]
+        );
+        assert_eq!(run("104,1125899906842624,99", &[]), vec![1125899906842624]);
+    }
+
+    #[test]
+    fn relative_writes() {
+        // RB 10, IN to [rb+5], OUT [rb+5]
+        assert_eq!(run("109,10,203,5,204,5,99", &[17]), vec![17]);
+
+        // RB 2000, ADD 3 + 4 into [rb-500], OUT [1500]
+        let mut program = read("109,2000,21101,3,4,-500,4,1500,99");
+        execute(&mut program);
+        assert_eq!(program.output, vec![7]);
+        assert_eq!(program.get(1500), 7);
+    }
+
+    #[test]
+    fn negative_relative_offsets() {
+        // RB 7, RB -3, OUT [rb-4] == [0]
+        assert_eq!(run("109,7,109,-3,204,-4,99", &[]), vec![109]);
+    }
+
+    #[test]
+    fn unset_memory_reads_as_zero() {
+        let program = read("99");
+        assert_eq!(program.get(1000), 0);
+
+        // OUT [1000], written by nothing
+        assert_eq!(run("4,1000,99", &[]), vec![0]);
+    }
+
+    #[test]
+    fn self_modifying_code() {
+        // ADD 104 + 0 -> [4] turns the QUIT at 4 into OUT 42 before it is reached
+        let mut program = read("1101,104,0,4,99,42,99");
+        execute(&mut program);
+        assert_eq!(program.output, vec![42]);
+    }
+
+    #[test]
+    fn detects_self_modification() {
+        // ADD 104 + 0 -> [4] overwrites the QUIT at 4 before it ran, then executes it
+        let mut program = read("1101,104,0,4,99,42,99");
+        program.detect_self_modification();
+        execute(&mut program);
+        assert_eq!(
+            program.self_modifications(),
+            &[smc::Modification::ExecutedWritten {
+                pc: 4,
+                address: 4,
+                value: 104
+            }]
+        );
+
+        // ADD [1] + 1 -> [1] increments its own operand
+        let mut program = read("1001,1,1,1,99");
+        program.detect_self_modification();
+        execute(&mut program);
+        assert_eq!(
+            program.self_modifications(),
+            &[smc::Modification::OverwroteCode {
+                pc: 0,
+                address: 1,
+                value: 2
+            }]
+        );
+
+        // ADD 0 + 99 -> [4] writes the QUIT that runs next
+        let mut program = read("1101,0,99,4,0");
+        program.detect_self_modification();
+        execute(&mut program);
+        assert_eq!(
+            program.self_modifications(),
+            &[smc::Modification::ExecutedWritten {
+                pc: 4,
+                address: 4,
+                value: 99
+            }]
+        );
+
+        // Without detection nothing is recorded
+        let mut program = read("1101,104,0,4,99,42,99");
+        execute(&mut program);
+        assert!(program.self_modifications().is_empty());
+    }
+
+    #[test]
+    fn instruction_sizes() {
+        let sizes: [(&str, usize); 9] = [
+            ("1,0,0,0", 4),
+            ("2,0,0,0", 4),
+            ("3,0", 2),
+            ("4,0", 2),
+            ("5,0,0", 3),
+            ("6,0,0", 3),
+            ("7,0,0,0", 4),
+            ("8,0,0,0", 4),
+            ("9,0", 2),
+        ];
+
+        for (source, size) in sizes.iter() {
+            let program = read(source);
+            assert_eq!(
+                Instruction::parse(&program).size,
+                *size,
+                "program {}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn unknown_opcode_panics() {
+        execute(&mut read("42,99"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_parameter_mode_panics() {
+        execute(&mut read("301,0,0,0,99"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn immediate_write_parameter_panics() {
+        execute(&mut read("11101,0,0,0,99"));
+    }
+
+    #[test]
+    #[should_panic(expected = "IN must write in POSITION or RELATIVE mode")]
+    fn immediate_input_parameter_panics_on_parse() {
+        let mut program = read("103,0,99");
+        program.add_input(1);
+        Instruction::parse(&program);
+    }
+}
@@ -0,0 +1,287 @@
+//! Recording and replay of the I/O of Intcode programs
+//!
+//! When recording is enabled on a Program, every value it reads and writes and every memory cell set from outside is
+//! remembered together with the number of instructions executed at that moment. The session can be saved and
+//! replayed later on the same program without the logic that produced the input, or compared with another session
+//! to find where two runs start to differ.
+//!
+//! Sessions are saved as text, a header line with the hash of the program followed by one event per line:
+//!     # intcode session 8f0c1d2e3a4b5c6d
+//!     0 set 0 2
+//!     1520 in -1
+//!     1533 out 12
+
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use super::*;
+use crate::common::input_hash;
+
+/// Environment variable with the file to record the sessions of the puzzle solutions in
+pub const RECORD_VAR: &str = "ADVENT_RECORD";
+
+const HEADER: &str = "# intcode session";
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Event {
+    /// The program read value
+    Input { step: u64, value: CpuWidth },
+    /// The program wrote value
+    Output { step: u64, value: CpuWidth },
+    /// Memory at address was set to value from outside the program
+    Set {
+        step: u64,
+        address: usize,
+        value: CpuWidth,
+    },
+}
+
+impl Event {
+    /// Number of instructions executed before the event
+    pub fn step(&self) -> u64 {
+        match self {
+            Event::Input { step, .. } | Event::Output { step, .. } | Event::Set { step, .. } => {
+                *step
+            }
+        }
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::Input { step, value } => write!(f, "{} in {}", step, value),
+            Event::Output { step, value } => write!(f, "{} out {}", step, value),
+            Event::Set {
+                step,
+                address,
+                value,
+            } => write!(f, "{} set {} {}", step, address, value),
+        }
+    }
+}
+
+impl FromStr for Event {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Event, String> {
+        let fields = line.split_whitespace().collect::<Vec<&str>>();
+        let number = |ix: usize| -> Result<CpuWidth, String> {
+            let field = fields.get(ix).ok_or("Missing field")?;
+            field
+                .parse::<CpuWidth>()
+                .map_err(|e| format!("{} ({:?})", e, field))
+        };
+
+        let step = number(0)? as u64;
+        match (fields.get(1), fields.len()) {
+            (Some(&"in"), 3) => Ok(Event::Input {
+                step,
+                value: number(2)?,
+            }),
+            (Some(&"out"), 3) => Ok(Event::Output {
+                step,
+                value: number(2)?,
+            }),
+            (Some(&"set"), 4) => Ok(Event::Set {
+                step,
+                address: number(2)? as usize,
+                value: number(3)?,
+            }),
+            _ => Err(format!("Invalid event {:?}", line)),
+        }
+    }
+}
+
+/// Everything that went into and out of a program
+#[derive(Clone, PartialEq, Debug)]
+pub struct Session {
+    /// Hash of the program as it was when recording started
+    pub program: String,
+    pub events: Vec<Event>,
+}
+
+/// Hash of the loaded memory of a program, the same as the hash of its source
+fn program_hash(program: &Program) -> String {
+    let source = (0..program.size())
+        .map(|address| program.get(address).to_string())
+        .collect::<Vec<String>>()
+        .join(",");
+    input_hash(&source)
+}
+
+impl Session {
+    pub fn new(program: &Program) -> Session {
+        Session {
+            program: program_hash(program),
+            events: Vec::new(),
+        }
+    }
+
+    pub fn read(path: &Path) -> Result<Session, String> {
+        fs::read_to_string(path)
+            .map_err(|e| format!("Could not read {}: {}", path.display(), e))?
+            .parse()
+            .map_err(|e| format!("{}:{}", path.display(), e))
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        fs::write(path, self.to_string())
+            .map_err(|e| format!("Could not write {}: {}", path.display(), e))
+    }
+
+    /// Index of the first event that differs from the other session, None when both are the same
+    pub fn diff(&self, other: &Session) -> Option<usize> {
+        let length = self.events.len().max(other.events.len());
+        (0..length).find(|ix| self.events.get(*ix) != other.events.get(*ix))
+    }
+}
+
+impl fmt::Display for Session {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} {}", HEADER, self.program)?;
+        for event in self.events.iter() {
+            writeln!(f, "{}", event)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Session {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Session, String> {
+        let mut lines = text.lines().enumerate();
+        let program = lines
+            .next()
+            .and_then(|(_, header)| header.strip_prefix(HEADER))
+            .map(|hash| hash.trim().to_string())
+            .ok_or_else(|| format!("1: Expected {:?} header", HEADER))?;
+
+        let events = lines
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(ix, line)| line.parse().map_err(|e| format!("{}: {}", ix + 1, e)))
+            .collect::<Result<Vec<Event>, String>>()?;
+
+        Ok(Session { program, events })
+    }
+}
+
+/// Runs the program again with the input and memory changes of the session and returns the new session, which can
+/// be compared with the recorded one
+pub fn replay(program: &mut Program, session: &Session) -> Result<Session, String> {
+    if program_hash(program) != session.program {
+        return Err(format!(
+            "Session was recorded on another program ({} instead of {})",
+            session.program,
+            program_hash(program)
+        ));
+    }
+
+    program.record();
+    for event in session.events.iter() {
+        match *event {
+            Event::Input { value, .. } => program.add_input(value),
+            Event::Set {
+                step,
+                address,
+                value,
+            } => {
+                execute_until(program, step);
+                program.set(address, value);
+            }
+            Event::Output { .. } => {}
+        }
+    }
+    execute(program);
+
+    Ok(program.session().cloned().expect("Recording was started"))
+}
+
+/// Starts recording when the environment asks for it
+pub fn record_from_env(program: &mut Program) {
+    if env::var_os(RECORD_VAR).is_some() {
+        program.record();
+    }
+}
+
+/// Saves the recorded session in the file named by the environment
+pub fn save_from_env(program: &Program) {
+    if let (Some(path), Some(session)) = (env::var_os(RECORD_VAR), program.session()) {
+        let path = Path::new(&path);
+        session.write(path).unwrap_or_else(|e| panic!("{}", e));
+        println!(
+            "Recorded {} events in {}",
+            session.events.len(),
+            path.display()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Adds the input to memory cell 20 (set from outside) and outputs the sum, until the input is 0
+    const ACCUMULATE: &str = "3,19,1006,19,17,1,19,20,20,4,20,1105,1,0,0,0,0,99,0,0,0";
+
+    fn record(inputs: &[CpuWidth]) -> Session {
+        let mut program = read(ACCUMULATE);
+        program.record();
+        program.set(20, 100);
+        for input in inputs {
+            execute(&mut program);
+            program.add_input(*input);
+        }
+        execute(&mut program);
+        program.session().cloned().unwrap()
+    }
+
+    #[test]
+    fn records_io_with_steps() {
+        let session = record(&[5, 7, 0]);
+
+        assert_eq!(session.program, input_hash(ACCUMULATE));
+        assert_eq!(
+            session.events[..4],
+            [
+                Event::Set {
+                    step: 0,
+                    address: 20,
+                    value: 100
+                },
+                Event::Input { step: 0, value: 5 },
+                Event::Output {
+                    step: 3,
+                    value: 105
+                },
+                Event::Input { step: 5, value: 7 },
+            ]
+        );
+        assert_eq!(session.events.len(), 6);
+        assert_eq!(session.to_string().parse(), Ok(session));
+    }
+
+    #[test]
+    fn replays_and_diffs() {
+        let session = record(&[5, 7, 0]);
+
+        let replayed = replay(&mut read(ACCUMULATE), &session).unwrap();
+        assert_eq!(replayed.diff(&session), None);
+
+        // Another input changes everything from the second input on
+        let other = record(&[5, 8, 0]);
+        assert_eq!(other.diff(&session), Some(3));
+        assert_eq!(record(&[5, 7]).diff(&session), Some(5));
+
+        assert!(replay(&mut read("99"), &session).is_err());
+        assert!("1 in 2".parse::<Session>().is_err());
+        assert!(format!("{} 00\n1 jump 2", HEADER)
+            .parse::<Session>()
+            .unwrap_err()
+            .starts_with("2: "));
+    }
+}
@@ -0,0 +1,82 @@
+//! Runtime detection of self-modifying Intcode
+//!
+//! When enabled on a Program, every address that is executed as part of an instruction and every address
+//! written by an instruction is remembered. Writing into code that already ran, or running code that was
+//! written by the program itself, is recorded as a Modification.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use super::CpuWidth;
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Modification {
+    /// The instruction at pc wrote value into an address that was previously executed
+    OverwroteCode {
+        pc: usize,
+        address: usize,
+        value: CpuWidth,
+    },
+    /// The instruction at pc is executed while address (part of that instruction) was written by the program
+    ExecutedWritten {
+        pc: usize,
+        address: usize,
+        value: CpuWidth,
+    },
+}
+
+impl fmt::Display for Modification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Modification::OverwroteCode { pc, address, value } => write!(
+                f,
+                "PC {:5}: wrote {} into executed code at {}",
+                pc, value, address
+            ),
+            Modification::ExecutedWritten { pc, address, value } => write!(
+                f,
+                "PC {:5}: executing {} at {} which was written by the program",
+                pc, value, address
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct SelfModification {
+    executed: HashSet<usize>,
+    written: HashSet<usize>,
+    pub modifications: Vec<Modification>,
+}
+
+impl SelfModification {
+    pub fn new() -> SelfModification {
+        SelfModification::default()
+    }
+
+    /// Called before the instruction at pc with the given memory cells is executed
+    pub fn execute(&mut self, pc: usize, cells: &[CpuWidth]) {
+        for (offset, value) in cells.iter().enumerate() {
+            let address = pc + offset;
+
+            // Report each written cell only once until it is written again
+            if self.written.remove(&address) {
+                self.modifications.push(Modification::ExecutedWritten {
+                    pc,
+                    address,
+                    value: *value,
+                });
+            }
+            self.executed.insert(address);
+        }
+    }
+
+    /// Called when the instruction at pc writes value into address
+    pub fn write(&mut self, pc: usize, address: usize, value: CpuWidth) {
+        if self.executed.contains(&address) {
+            self.modifications
+                .push(Modification::OverwroteCode { pc, address, value });
+        }
+        self.written.insert(address);
+    }
+}
@@ -0,0 +1,206 @@
+//! Fuel needed to launch modules
+//!
+//! The fuel for a module is its mass divided by three, rounded down, minus two. Fuel has mass too, so the total fuel
+//! for a module includes fuel for its fuel, and for that fuel, until the extra mass needs no fuel. Masses are read
+//! from any reader one per line, so lists too large to keep in memory can be summed as they stream in.
+
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead};
+
+#[derive(Debug)]
+pub enum FuelError {
+    /// A line that is not a mass, line numbers start at 1
+    Parse {
+        line: usize,
+        text: String,
+    },
+    Io(io::Error),
+    /// A sum of fuel does not fit in 64 bits
+    Overflow,
+}
+
+impl fmt::Display for FuelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FuelError::Parse { line, text } => write!(f, "line {}: {:?} is not a mass", line, text),
+            FuelError::Io(error) => write!(f, "Could not read masses: {}", error),
+            FuelError::Overflow => write!(f, "Total fuel does not fit in 64 bits"),
+        }
+    }
+}
+
+impl Error for FuelError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FuelError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Fuel for the mass of a module only, masses below 9 need none
+pub fn module_fuel(mass: u64) -> u64 {
+    (mass / 3).saturating_sub(2)
+}
+
+/// Fuel for a module including the fuel for all the fuel that is added
+pub fn total_fuel(mass: u64) -> u64 {
+    let mut total = 0;
+    let mut fuel = module_fuel(mass);
+    while fuel > 0 {
+        // The total never exceeds half the mass, so it can not overflow
+        total += fuel;
+        fuel = module_fuel(fuel);
+    }
+    total
+}
+
+/// Fuel of a single module
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ModuleFuel {
+    pub mass: u64,
+    /// Fuel for the mass of the module
+    pub fuel: u64,
+    /// Fuel for the mass of the module and its fuel
+    pub total: u64,
+}
+
+impl ModuleFuel {
+    pub fn new(mass: u64) -> ModuleFuel {
+        ModuleFuel {
+            mass,
+            fuel: module_fuel(mass),
+            total: total_fuel(mass),
+        }
+    }
+
+    /// The part of the total that is needed for the fuel itself
+    pub fn fuel_for_fuel(&self) -> u64 {
+        self.total - self.fuel
+    }
+}
+
+impl fmt::Display for ModuleFuel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>12} {:>12} {:>12} {:>12}",
+            self.mass,
+            self.fuel,
+            self.fuel_for_fuel(),
+            self.total
+        )
+    }
+}
+
+/// Sums of the fuel of all modules
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Totals {
+    pub modules: usize,
+    pub fuel: u64,
+    pub total: u64,
+}
+
+impl Totals {
+    pub fn add(&mut self, module: &ModuleFuel) -> Result<(), FuelError> {
+        self.fuel = self
+            .fuel
+            .checked_add(module.fuel)
+            .ok_or(FuelError::Overflow)?;
+        self.total = self
+            .total
+            .checked_add(module.total)
+            .ok_or(FuelError::Overflow)?;
+        self.modules += 1;
+        Ok(())
+    }
+}
+
+impl fmt::Display for Totals {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>12} {:>12} {:>12} {:>12}",
+            format!("{} modules", self.modules),
+            self.fuel,
+            self.total - self.fuel,
+            self.total
+        )
+    }
+}
+
+/// Header for a breakdown of ModuleFuel and Totals lines
+pub const BREAKDOWN_HEADER: &str = "        mass         fuel fuel of fuel        total";
+
+/// Masses read line by line, empty lines are skipped
+pub fn masses<R: BufRead>(reader: R) -> impl Iterator<Item = Result<u64, FuelError>> {
+    reader
+        .lines()
+        .enumerate()
+        .filter_map(|(ix, line)| match line {
+            Ok(line) if line.trim().is_empty() => None,
+            Ok(line) => Some(line.trim().parse::<u64>().map_err(|_| FuelError::Parse {
+                line: ix + 1,
+                text: line,
+            })),
+            Err(error) => Some(Err(FuelError::Io(error))),
+        })
+}
+
+/// Fuel of every module read from the reader
+pub fn breakdown<R: BufRead>(reader: R) -> impl Iterator<Item = Result<ModuleFuel, FuelError>> {
+    masses(reader).map(|mass| mass.map(ModuleFuel::new))
+}
+
+/// Sums the fuel of all modules read from the reader, without keeping them
+pub fn totals<R: BufRead>(reader: R) -> Result<Totals, FuelError> {
+    let mut totals = Totals::default();
+    for module in breakdown(reader) {
+        totals.add(&module?)?;
+    }
+    Ok(totals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn puzzle_examples() {
+        assert_eq!(module_fuel(12), 2);
+        assert_eq!(module_fuel(14), 2);
+        assert_eq!(module_fuel(1969), 654);
+        assert_eq!(module_fuel(100756), 33583);
+        assert_eq!(module_fuel(8), 0);
+
+        assert_eq!(total_fuel(14), 2);
+        assert_eq!(total_fuel(1969), 966);
+        assert_eq!(total_fuel(100756), 50346);
+        assert_eq!(ModuleFuel::new(1969).fuel_for_fuel(), 312);
+
+        // No recursion, so huge masses are fine
+        assert!(total_fuel(u64::MAX) < u64::MAX / 2);
+    }
+
+    #[test]
+    fn streams_masses() {
+        let summed = totals("12\n\n14\n 1969\n100756\n".as_bytes()).unwrap();
+        assert_eq!(
+            summed,
+            Totals {
+                modules: 4,
+                fuel: 2 + 2 + 654 + 33583,
+                total: 2 + 2 + 966 + 50346,
+            }
+        );
+
+        match totals("12\n-14\n".as_bytes()) {
+            Err(FuelError::Parse { line, text }) => assert_eq!((line, text.as_str()), (2, "-14")),
+            other => panic!("Expected a parse error, got {:?}", other),
+        }
+
+        let huge = format!("{}\n", u64::MAX).repeat(4);
+        assert!(matches!(totals(huge.as_bytes()), Err(FuelError::Overflow)));
+    }
+}
@@ -1,36 +1,35 @@
 use crate::common;
 
-fn fuel_required(mass: i64) -> i64 {
-    (mass / 3) as i64 - 2
+pub mod fuel;
+
+pub fn masses(input: &str) -> Vec<u64> {
+    fuel::masses(input.as_bytes())
+        .collect::<Result<Vec<u64>, _>>()
+        .unwrap_or_else(|e| panic!("Invalid mass in input, {}", e))
+}
+
+fn totals(input: &str) -> fuel::Totals {
+    fuel::totals(input.as_bytes()).unwrap_or_else(|e| panic!("Invalid mass in input, {}", e))
 }
 
-fn fuel_required_including_fuel(mass: i64) -> i64 {
-    let fuel = fuel_required(mass);
+pub fn solve1(input: &str) -> u64 {
+    totals(input).fuel
+}
 
-    if fuel <= 0 {
-        mass
-    } else {
-        mass + fuel_required_including_fuel(fuel)
-    }
+pub fn solve2(input: &str) -> u64 {
+    totals(input).total
 }
 
 pub fn part1() {
-    let input = common::import("src/day1/input.txt");
-    let mut sum = 0;
-    for mass in input {
-        sum += fuel_required(mass);
-    }
-    println!("Day 1 part 1 : Total fuel required is {} ", sum);
+    println!(
+        "Day 1 part 1 : Total fuel required is {} ",
+        solve1(&common::puzzle_input(1))
+    );
 }
 
 pub fn part2() {
-    let input = common::import("src/day1/input.txt");
-    let mut sum = 0;
-    for mass in input {
-        sum += fuel_required_including_fuel(fuel_required(mass));
-    }
     println!(
         "Day 1 part 2 : Total fuel required (including fuel) is {}",
-        sum
+        solve2(&common::puzzle_input(1))
     );
 }
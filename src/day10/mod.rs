@@ -7,58 +7,15 @@ macro_rules! vec_of_strings {
 }
 
 fn input() -> Vec<String> {
-    vec_of_strings![
-		"..............#.#...............#....#....",
-		"#.##.......#....#.#..##........#...#......",
-		"..#.....#....#..#.#....#.....#.#.##..#..#.",
-		"...........##...#...##....#.#.#....#.##..#",
-		"....##....#...........#..#....#......#.###",
-		".#...#......#.#.#.#...#....#.##.##......##",
-		"#.##....#.....#.....#...####........###...",
-		".####....#.......#...##..#..#......#...#..",
-		"...............#...........#..#.#.#.......",
-		"........#.........##...#..........#..##...",
-		"...#..................#....#....##..#.....",
-		".............#..#.#.........#........#.##.",
-		"...#.#....................##..##..........",
-		".....#.#...##..............#...........#..",
-		"......#..###.#........#.....#.##.#......#.",
-		"#......#.#.....#...........##.#.....#..#.#",
-		".#.............#..#.....##.....###..#..#..",
-		".#...#.....#.....##.#......##....##....#..",
-		".........#.#..##............#..#...#......",
-		"..#..##...#.#..#....#..#.#.......#.##.....",
-		"#.......#.#....#.#..##.#...#.......#..###.",
-		".#..........#...##.#....#...#.#.........#.",
-		"..#.#.......##..#.##..#.......#.###.......",
-		"...#....###...#......#..#.....####........",
-		".............#.#..........#....#......#...",
-		"#................#..................#.###.",
-		"..###.........##...##..##.................",
-		".#.........#.#####..#...##....#...##......",
-		"........#.#...#......#.................##.",
-		".##.....#..##.##.#....#....#......#.#....#",
-		".....#...........#.............#.....#....",
-		"........#.##.#...#.###.###....#.#......#..",
-		"..#...#.......###..#...#.##.....###.....#.",
-		"....#.....#..#.....#...#......###...###...",
-		"#..##.###...##.....#.....#....#...###..#..",
-		"........######.#...............#...#.#...#",
-		"...#.....####.##.....##...##..............",
-		"###..#......#...............#......#...#..",
-		"#..#...#.#........#.#.#...#..#....#.#.####",
-		"#..#...#..........##.#.....##........#.#..",
-		"........#....#..###..##....#.#.......##..#",
-		".................##............#.......#.."
-	]
-}
-
-struct Asteroid {
-    pub location: Vector,
+    lines(&puzzle_input(10))
+}
+
+pub struct Asteroid {
+    pub location: Point,
 }
 
 impl Asteroid {
-    pub fn new(location: Vector) -> Asteroid {
+    pub fn new(location: Point) -> Asteroid {
         Asteroid { location: location }
     }
 }
@@ -69,18 +26,14 @@ impl std::cmp::PartialEq for Asteroid {
     }
 }
 
-fn parse_asteroids(input: &Vec<String>) -> Vec<Asteroid> {
+pub fn parse_asteroids(input: &[String]) -> Vec<Asteroid> {
     let mut asteroids = Vec::<Asteroid>::new();
 
     for y in 0..input.len() {
         let line = &input[y].as_bytes();
         for x in 0..line.len() {
             if line[x] == '#' as u8 {
-                asteroids.push(Asteroid::new(Vector {
-                    x: x as i32,
-                    y: y as i32,
-                    z: 0,
-                }));
+                asteroids.push(Asteroid::new(Point::new(x as i32, y as i32)));
             }
         }
     }
@@ -121,12 +74,12 @@ fn generate_paths(source: &Asteroid, targets: &Vec<Asteroid>) -> Vec<Path> {
 	This creates a vector of tuples which contains for each location a collection of Paths to other asteroids
 	Asteroids that are blocked by other asteroids (same angle, closer by) are not included in the latter collection
 */
-fn process_asteroids(asteroids: &Vec<Asteroid>) -> Vec<(Vector, Vec<Path>)> {
-    let mut result = Vec::<(Vector, Vec<Path>)>::new();
+fn process_asteroids(asteroids: &Vec<Asteroid>) -> Vec<(Point, Vec<Path>)> {
+    let mut result = Vec::<(Point, Vec<Path>)>::new();
 
     for source in asteroids {
         let paths = generate_paths(source, asteroids);
-        result.push((source.location.clone(), paths));
+        result.push((source.location, paths));
     }
 
     result
@@ -142,8 +95,11 @@ pub fn test() {
     }
 }
 
-pub fn part1() {
-    let mut asteroids_in_view = process_asteroids(&parse_asteroids(&input()));
+/*
+	The location with the most asteroids in view and that number of asteroids
+*/
+fn best_location(input: &[String]) -> (Point, usize) {
+    let mut asteroids_in_view = process_asteroids(&parse_asteroids(input));
 
     // Sort by number of asteroids in view, ascending
     asteroids_in_view.sort_by(|a, b| a.1.len().cmp(&b.1.len()));
@@ -152,10 +108,35 @@ pub fn part1() {
         .last()
         .expect("No asteroids found, bollox!");
 
+    (best_location.0, best_location.1.len())
+}
+
+pub fn lines(input: &str) -> Vec<String> {
+    input.lines().map(String::from).collect()
+}
+
+pub fn solve1(input: &str) -> usize {
+    best_location(&lines(input)).1
+}
+
+/*
+	Place the laser at the best location and find the 200th asteroid destroyed
+*/
+pub fn solve2(input: &str) -> Option<i32> {
+    let input = lines(input);
+    let laser = Asteroid::new(best_location(&input).0);
+
+    vaporize(parse_asteroids(&input), &laser)
+        .get(199)
+        .map(|p| p.x * 100 + p.y)
+}
+
+pub fn part1() {
+    let (location, in_view) = best_location(&input());
+
     println!(
         "Best location {} with {} targetable asteroids",
-        best_location.0,
-        best_location.1.len()
+        location, in_view
     );
 }
 
@@ -198,46 +179,102 @@ pub fn part2() {
     // };
 
     // Place the laser at location obtained from part 1
-    let mut galaxy = parse_asteroids(&input());
     let laser = Asteroid {
-        location: Vector { x: 26, y: 36, z: 0 },
+        location: Point::new(26, 36),
     };
+    let order = vaporize(parse_asteroids(&input()), &laser);
 
-    let mut destroyed = 0;
+    let p = laser.location.path(&order[199]);
+    println!(
+        "Destroyed asteroid 200 was {} which results in {}",
+        p,
+        p.target.x * 100 + p.target.y
+    );
+}
+
+/*
+	Rotate the laser clockwise starting up, destroying the closest asteroid in view every time it passes a direction.
+	Returns the locations of the asteroids in the order they are destroyed
+*/
+fn vaporize(mut galaxy: Vec<Asteroid>, laser: &Asteroid) -> Vec<Point> {
+    galaxy.retain(|a| a != laser);
 
-    while galaxy.len() > 0 && destroyed < 200 {
+    let mut destroyed = Vec::new();
+
+    while !galaxy.is_empty() {
         // Generate paths to targettable asteroids
-        let mut paths = generate_paths(&laser, &galaxy);
+        let mut paths = generate_paths(laser, &galaxy);
 
-        // Sort paths by increasing angle
-        paths.sort_by(|a, b| {
-            a.angle
-                .partial_cmp(&b.angle)
-                .expect("The world ceased to exist while sorting paths")
-        });
+        // Sort paths clockwise
+        paths.sort_by_key(|p| p.direction);
 
         for p in paths {
-            // Shoot them ...
-            destroyed += 1;
-
-            // and remove from galaxy
-            galaxy.remove(
-                galaxy
-                    .iter()
-                    .position(|a| a.location == p.target)
-                    .expect("Eeks, asteroid not found in galaxy"),
-            );
-
-            // Enough with the shooting already
-            if destroyed == 200 {
-                println!(
-                    "Destroyed asteroid {} was {} which results in {}",
-                    destroyed,
-                    p,
-                    p.target.x * 100 + p.target.y
-                );
-                break;
-            }
+            // Shoot them and remove from galaxy
+            galaxy.retain(|a| a.location != p.target);
+            destroyed.push(p.target);
         }
     }
+
+    destroyed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn large_example() -> Vec<String> {
+        vec_of_strings![
+            ".#..##.###...#######",
+            "##.############..##.",
+            ".#.######.########.#",
+            ".###.#######.####.#.",
+            "#####.##.#.##.###.##",
+            "..#####..#.#########",
+            "####################",
+            "#.####....###.#.#.##",
+            "##.#################",
+            "#####.##.###..####..",
+            "..######..##.#######",
+            "####.##.####...##..#",
+            ".#####..#.######.###",
+            "##...#.##########...",
+            "#.##########.#######",
+            ".####.#.###.###.#.##",
+            "....##.##.###..#####",
+            ".#.#.###########.###",
+            "#.#.#.#####.####.###",
+            "###.##.####.##.#..##"
+        ]
+    }
+
+    #[test]
+    fn best_location_sees_most_asteroids() {
+        let targetable = process_asteroids(&parse_asteroids(&large_example()));
+        let best = targetable
+            .iter()
+            .max_by_key(|(_, paths)| paths.len())
+            .unwrap();
+
+        assert_eq!(best.0, Point::new(11, 13));
+        assert_eq!(best.1.len(), 210);
+    }
+
+    #[test]
+    fn vaporizes_clockwise() {
+        let laser = Asteroid::new(Point::new(11, 13));
+        let order = vaporize(parse_asteroids(&large_example()), &laser);
+
+        assert_eq!(order.len(), 299);
+        assert_eq!(order[0], Point::new(11, 12));
+        assert_eq!(order[1], Point::new(12, 1));
+        assert_eq!(order[2], Point::new(12, 2));
+        assert_eq!(order[9], Point::new(12, 8));
+        assert_eq!(order[19], Point::new(16, 0));
+        assert_eq!(order[49], Point::new(16, 9));
+        assert_eq!(order[99], Point::new(10, 16));
+        assert_eq!(order[198], Point::new(9, 6));
+        assert_eq!(order[199], Point::new(8, 2));
+        assert_eq!(order[200], Point::new(10, 9));
+        assert_eq!(order[298], Point::new(11, 1));
+    }
 }
@@ -1,16 +1,6 @@
-use std::collections::HashMap;
-
 use crate::common::*;
 use crate::cpu::*;
 
-#[derive(PartialEq, Copy, Clone)]
-enum Direction {
-    UP = 0,
-    LEFT = 1,
-    DOWN = 2,
-    RIGHT = 3,
-}
-
 #[derive(PartialEq, Copy, Clone)]
 enum Color {
     BLACK = 0,
@@ -24,101 +14,39 @@ impl Default for Color {
 }
 
 struct Robot {
-    location: Vector,
-    direction: Direction,
-    world: HashMap<Vector, Color>,
+    location: Point,
+    heading: Heading,
+    world: Grid<Color>,
 }
 
 impl Robot {
     pub fn new() -> Robot {
         Robot {
-            location: Vector { x: 0, y: 0, z: 0 },
-            direction: Direction::UP,
-            world: HashMap::<_, _>::new(),
+            location: Point::new(0, 0),
+            heading: Heading::North,
+            world: Grid::new(),
         }
     }
 
     pub fn paint(&mut self, color: Color) {
-        self.world.insert(self.location.clone(), color);
-    }
-
-    fn turn(&mut self, dir: Direction) {
-        match dir {
-            Direction::LEFT => {
-                self.direction = [
-                    Direction::LEFT,
-                    Direction::DOWN,
-                    Direction::RIGHT,
-                    Direction::UP,
-                ][self.direction as usize];
-            }
-            Direction::RIGHT => {
-                self.direction = [
-                    Direction::RIGHT,
-                    Direction::UP,
-                    Direction::LEFT,
-                    Direction::DOWN,
-                ][self.direction as usize];
-            }
-            _ => panic!("Robot Can only turn left or right"),
-        }
+        self.world.insert(self.location, color);
     }
 
     fn forward(&mut self) {
-        match self.direction {
-            Direction::UP => {
-                self.location.y -= 1;
-            }
-            Direction::LEFT => {
-                self.location.x -= 1;
-            }
-            Direction::DOWN => {
-                self.location.y += 1;
-            }
-            Direction::RIGHT => {
-                self.location.x += 1;
-            }
-        }
+        self.location = self.heading.step(&self.location, YAxis::Down);
     }
 
-    pub fn color(&self, location: &Vector) -> Color {
-        if self.world.contains_key(location) {
-            self.world[location]
-        } else {
-            Color::BLACK
-        }
+    pub fn color(&self, location: &Point) -> Color {
+        self.world.get(location).cloned().unwrap_or_default()
     }
 
     pub fn current(&mut self) -> Color {
         self.color(&self.location)
     }
 
-    /*
-        returns the top left and bottom right corner of the map so far
-    */
-    pub fn extends(&self) -> (Vector, Vector) {
-        let x_min = self.world.keys().min_by(|a, b| a.x.cmp(&b.x)).unwrap().x;
-        let x_max = self.world.keys().max_by(|a, b| a.x.cmp(&b.x)).unwrap().x;
-        let y_min = self.world.keys().min_by(|a, b| a.y.cmp(&b.y)).unwrap().y;
-        let y_max = self.world.keys().max_by(|a, b| a.y.cmp(&b.y)).unwrap().y;
-
-        (
-            Vector {
-                x: x_min,
-                y: y_min,
-                z: 0,
-            },
-            Vector {
-                x: x_max,
-                y: y_max,
-                z: 0,
-            },
-        )
-    }
-
-    pub fn next(&mut self, color: Color, dir: Direction) {
+    pub fn next(&mut self, color: Color, turn: Turn) {
         self.paint(color);
-        self.turn(dir);
+        self.heading = self.heading.turn(turn);
         self.forward();
     }
 
@@ -127,40 +55,63 @@ impl Robot {
     }
 }
 
+fn trail(robot: &Robot) -> String {
+    let (top_left, bottom_right) = robot.world.extends().expect("Nothing painted yet");
+    robot.world.render_area(
+        &(top_left - Point::new(1, 1)),
+        &(bottom_right + Point::new(1, 1)),
+        |_, color| if color == Some(&Color::WHITE) { '#' } else { '.' },
+    )
+}
+
+/// The painted hull with the robot on it
+fn hull(robot: &Robot) -> String {
+    let (top_left, bottom_right) = robot.world.extends().unwrap_or((robot.location, robot.location));
+    let top_left = Point::new(
+        std::cmp::min(top_left.x, robot.location.x),
+        std::cmp::min(top_left.y, robot.location.y),
+    );
+    let bottom_right = Point::new(
+        std::cmp::max(bottom_right.x, robot.location.x),
+        std::cmp::max(bottom_right.y, robot.location.y),
+    );
+
+    robot.world.render_area(
+        &(top_left - Point::new(1, 1)),
+        &(bottom_right + Point::new(1, 1)),
+        |location, color| {
+            if *location == robot.location {
+                robot.heading.arrow()
+            } else if color == Some(&Color::WHITE) {
+                '#'
+            } else {
+                '.'
+            }
+        },
+    )
+}
+
 fn draw_trail(robot: &Robot) {
-    let extends = robot.extends();
-    for y in extends.0.y - 1..=extends.1.y + 1 {
-        for x in extends.0.x - 1..=extends.1.x + 1 {
-            print!(
-                "{}",
-                if robot.color(&Vector { x: x, y: y, z: 0 }) == Color::WHITE {
-                    '#'
-                } else {
-                    '.'
-                }
-            );
-        }
-        println!("");
-    }
+    print!("{}", trail(robot));
 }
 
 pub fn test() {
     let mut robot = Robot::new();
 
-    robot.next(Color::WHITE, Direction::LEFT);
-    robot.next(Color::BLACK, Direction::LEFT);
-    robot.next(Color::WHITE, Direction::LEFT);
-    robot.next(Color::WHITE, Direction::LEFT);
-    robot.next(Color::BLACK, Direction::RIGHT);
-    robot.next(Color::WHITE, Direction::LEFT);
-    robot.next(Color::WHITE, Direction::LEFT);
+    robot.next(Color::WHITE, Turn::Left);
+    robot.next(Color::BLACK, Turn::Left);
+    robot.next(Color::WHITE, Turn::Left);
+    robot.next(Color::WHITE, Turn::Left);
+    robot.next(Color::BLACK, Turn::Right);
+    robot.next(Color::WHITE, Turn::Left);
+    robot.next(Color::WHITE, Turn::Left);
 
     draw_trail(&robot);
 
     println!("Painted {} panels on the test hull", robot.painted());
 }
 
-fn run_robot_program(robot: &mut Robot, program: &mut Program) {
+fn run_robot_program(robot: &mut Robot, program: &mut Program, renderer: &mut dyn Renderer) {
     while !program.is_finished() {
         if program.is_waiting() {
             program.flush();
@@ -179,33 +130,47 @@ fn run_robot_program(robot: &mut Robot, program: &mut Program) {
                 Color::WHITE
             },
             if program.get_output(1).expect("No output") == 0 {
-                Direction::LEFT
+                Turn::Left
             } else {
-                Direction::RIGHT
+                Turn::Right
             },
         );
+
+        renderer.draw(|| hull(robot));
     }
 }
 
-pub fn part1() {
-    let mut program = read(&import_lines("src/day11/input.txt"));
+fn paint_hull(input: &str, start: Color, renderer: &mut dyn Renderer) -> Robot {
+    let mut program = read(input);
 
     let mut robot = Robot::new();
+    robot.paint(start);
 
-    run_robot_program(&mut robot, &mut program);
+    run_robot_program(&mut robot, &mut program, renderer);
+    robot
+}
 
-    draw_trail(&robot);
+pub fn solve1(input: &str) -> usize {
+    paint_hull(input, Color::BLACK, &mut NullRenderer).painted()
+}
 
-    println!("Painted {} panels on the hull", robot.painted());
+/// The registration identifier as text
+pub fn solve2(input: &str) -> String {
+    trail(&paint_hull(input, Color::WHITE, &mut NullRenderer))
 }
 
-pub fn part2() {
-    let mut program = read(&import_lines("src/day11/input.txt"));
+pub fn part1() {
+    let mut renderer = renderer();
+    let robot = paint_hull(&puzzle_input(11), Color::BLACK, &mut *renderer);
+    renderer.finish();
 
-    let mut robot = Robot::new();
-    robot.paint(Color::WHITE);
+    println!("Painted {} panels on the hull", robot.painted());
+}
 
-    run_robot_program(&mut robot, &mut program);
+pub fn part2() {
+    let mut renderer = renderer();
+    let robot = paint_hull(&puzzle_input(11), Color::WHITE, &mut *renderer);
+    renderer.finish();
 
     draw_trail(&robot);
 
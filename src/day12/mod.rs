@@ -1,7 +1,7 @@
-use crate::common::Vector;
+use crate::common::{puzzle_input, Point3};
 
 #[derive(PartialEq, Clone)]
-enum MoonId {
+pub enum MoonId {
     IO,
     EUROPA,
     GANYMEDE,
@@ -9,23 +9,23 @@ enum MoonId {
 }
 
 #[derive(Clone)]
-struct Moon {
+pub struct Moon {
     pub id: MoonId,
-    pub location: Vector,
-    pub velocity: Vector,
+    pub location: Point3<i32>,
+    pub velocity: Point3<i32>,
 }
 
 impl Moon {
     pub fn new(id: MoonId, x: i32, y: i32, z: i32) -> Moon {
         Moon {
             id: id,
-            location: Vector { x: x, y: y, z: z },
-            velocity: Vector { x: 0, y: 0, z: 0 },
+            location: Point3::new(x, y, z),
+            velocity: Point3::default(),
         }
     }
 
     pub fn energy(&self) -> i32 {
-        self.location.magnitude() * self.velocity.magnitude()
+        self.location.manhattan_length() * self.velocity.manhattan_length()
     }
 
     pub fn gravity(this: &mut Moon, that: &mut Moon) {
@@ -50,9 +50,7 @@ impl Moon {
     }
 
     pub fn step(&mut self) {
-        self.location.x += self.velocity.x;
-        self.location.y += self.velocity.y;
-        self.location.z += self.velocity.z;
+        self.location += self.velocity;
     }
 }
 
@@ -111,12 +109,42 @@ fn lcm(a: i64, b: i64) -> i64 {
     (a * b) / gcd(a, b)
 }
 
-fn moons() -> (Moon, Moon, Moon, Moon) {
+/*
+	Parse a moon location like <x=-4, y=3, z=15>
+*/
+fn parse_location(line: &str) -> Point3<i32> {
+    let components = line
+        .trim()
+        .trim_start_matches('<')
+        .trim_end_matches('>')
+        .split(',')
+        .map(|c| {
+            c.split('=')
+                .nth(1)
+                .and_then(|v| v.trim().parse::<i32>().ok())
+                .expect("Invalid moon location")
+        })
+        .collect::<Vec<i32>>();
+
+    assert_eq!(components.len(), 3, "Invalid moon location {}", line);
+    Point3::new(components[0], components[1], components[2])
+}
+
+pub fn moons(input: &str) -> (Moon, Moon, Moon, Moon) {
+    let locations = input
+        .lines()
+        .map(parse_location)
+        .collect::<Vec<Point3<i32>>>();
+    let moon = |id, ix: usize| {
+        let l = locations[ix];
+        Moon::new(id, l.x, l.y, l.z)
+    };
+
     (
-        Moon::new(MoonId::IO, -4, 3, 15),
-        Moon::new(MoonId::EUROPA, -11, -10, 13),
-        Moon::new(MoonId::GANYMEDE, 2, 2, 18),
-        Moon::new(MoonId::CALLISTO, 7, -1, 0),
+        moon(MoonId::IO, 0),
+        moon(MoonId::EUROPA, 1),
+        moon(MoonId::GANYMEDE, 2),
+        moon(MoonId::CALLISTO, 3),
     )
 }
 
@@ -138,8 +166,16 @@ pub fn test() {
     );
 }
 
+pub fn solve1(input: &str) -> i32 {
+    let (mut io, mut europa, mut ganymede, mut callisto) = moons(input);
+
+    simulate(&mut io, &mut europa, &mut ganymede, &mut callisto, 1000);
+
+    io.energy() + europa.energy() + ganymede.energy() + callisto.energy()
+}
+
 pub fn part1() {
-    let (mut io, mut europa, mut ganymede, mut callisto) = moons();
+    let (mut io, mut europa, mut ganymede, mut callisto) = moons(&puzzle_input(12));
 
     simulate(&mut io, &mut europa, &mut ganymede, &mut callisto, 1000);
 
@@ -153,7 +189,7 @@ pub fn part1() {
     );
 }
 
-pub fn part2() {
+pub fn solve2(input: &str) -> i64 {
     // let initial_state = (
     // 	Moon::new(MoonId::IO, -1, 0, 2),
     // 	Moon::new(MoonId::EUROPA, 2, -10, -7),
@@ -168,7 +204,7 @@ pub fn part2() {
     // 	Moon::new(MoonId::CALLISTO, 9, -8, -3),
     // );
 
-    let initial_state = moons();
+    let initial_state = moons(input);
 
     let x_initial = vec![
         (initial_state.0.location.x, initial_state.0.velocity.x),
@@ -192,7 +228,7 @@ pub fn part2() {
     ];
 
     let mut moons = initial_state.clone();
-    let mut periods = Vector::new(0, 0, 0);
+    let mut periods = Point3::default();
     let mut generation = 0;
     while periods.x == 0 || periods.y == 0 || periods.z == 0 {
         simulate(&mut moons.0, &mut moons.1, &mut moons.2, &mut moons.3, 1);
@@ -230,8 +266,12 @@ pub fn part2() {
         }
     }
 
+    lcm(lcm(periods.x.into(), periods.y.into()), periods.z.into())
+}
+
+pub fn part2() {
     println!(
         "Repeating universe history after {} generations",
-        lcm(lcm(periods.x.into(), periods.y.into()), periods.z.into())
+        solve2(&puzzle_input(12))
     );
 }
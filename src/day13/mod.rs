@@ -1,11 +1,9 @@
-use std::{thread, time};
 use std::collections::hash_map::Entry;
-use std::collections::HashMap;
 
 use crate::common::*;
 use crate::cpu::*;
 
-type MapType = HashMap<Vector, Tile>;
+type MapType = Grid<Tile>;
 
 #[derive(PartialEq)]
 enum TileType {
@@ -29,24 +27,26 @@ impl TileType {
     }
 }
 
+impl TileType {
+    pub fn to_char(&self) -> char {
+        match *self {
+            TileType::EMPTY => ' ',
+            TileType::WALL => '#',
+            TileType::BLOCK => '*',
+            TileType::HPADDLE => '_',
+            TileType::BALL => 'o',
+        }
+    }
+}
+
 impl std::fmt::Display for TileType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match *self {
-                TileType::EMPTY => " ",
-                TileType::WALL => "#",
-                TileType::BLOCK => "*",
-                TileType::HPADDLE => "_",
-                TileType::BALL => "o",
-            }
-        )
+        write!(f, "{}", self.to_char())
     }
 }
 
 struct Tile {
-    location: Vector,
+    location: Point,
     tile_type: TileType,
 }
 
@@ -59,7 +59,7 @@ impl std::fmt::Display for Tile {
 impl Tile {
     pub fn new(x: i32, y: i32, tile_type: TileType) -> Tile {
         Tile {
-            location: Vector { x: x, y: y, z: 0 },
+            location: Point::new(x, y),
             tile_type: tile_type,
         }
     }
@@ -68,13 +68,13 @@ impl Tile {
         chunk[0] == -1
     }
 
-    pub fn read(input: &Vec<CpuWidth>) -> HashMap<Vector, Tile> {
+    pub fn read(input: &[CpuWidth]) -> MapType {
         input
             .chunks(3)
             .filter(|chunk| !Tile::is_score_chunk(chunk))
             .map(|chunk| {
                 (
-                    Vector::new(chunk[0] as i32, chunk[1] as i32, 0),
+                    Point::new(chunk[0] as i32, chunk[1] as i32),
                     Tile::new(
                         chunk[0] as i32,
                         chunk[1] as i32,
@@ -90,8 +90,8 @@ struct Map {
     world: MapType,
     score: CpuWidth,
 
-    paddle: Option<Vector>,
-    ball: Option<Vector>,
+    paddle: Option<Point>,
+    ball: Option<Point>,
 }
 
 impl Map {
@@ -104,26 +104,6 @@ impl Map {
         }
     }
 
-    pub fn extends(&self) -> (Vector, Vector) {
-        let x_min = self.world.keys().min_by(|a, b| a.x.cmp(&b.x)).unwrap().x;
-        let x_max = self.world.keys().max_by(|a, b| a.x.cmp(&b.x)).unwrap().x;
-        let y_min = self.world.keys().min_by(|a, b| a.y.cmp(&b.y)).unwrap().y;
-        let y_max = self.world.keys().max_by(|a, b| a.y.cmp(&b.y)).unwrap().y;
-
-        (
-            Vector {
-                x: x_min,
-                y: y_min,
-                z: 0,
-            },
-            Vector {
-                x: x_max,
-                y: y_max,
-                z: 0,
-            },
-        )
-    }
-
     fn extract_score(&mut self, input: &Vec<CpuWidth>) {
         if let Some(score_chunk) = input.chunks(3).find(|chunk| Tile::is_score_chunk(chunk)) {
             self.score = score_chunk[2];
@@ -135,72 +115,68 @@ impl Map {
 
         let tiles = Tile::read(input);
 
-        for tile in tiles {
-            if tile.1.tile_type == TileType::BALL {
-                self.ball = Some(tile.0.clone());
-            } else if tile.1.tile_type == TileType::HPADDLE {
-                self.paddle = Some(tile.0.clone());
+        for (location, tile) in tiles.iter() {
+            if tile.tile_type == TileType::BALL {
+                self.ball = Some(location);
+            } else if tile.tile_type == TileType::HPADDLE {
+                self.paddle = Some(location);
             }
+        }
 
-            self.world.insert(tile.0, tile.1);
+        for (location, tile) in tiles {
+            self.world.insert(location, tile);
         }
     }
 }
 
 impl std::fmt::Display for Map {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let extends = self.extends();
-        for y in extends.0.y..=extends.1.y {
-            for x in extends.0.x..=extends.1.x {
-                write!(f, "{}", self.world[&Vector::new(x, y, 0)].tile_type).expect("IO error");
-            }
-            writeln!(f).expect("IO error");
-        }
-        std::fmt::Result::Ok(())
+        write!(
+            f,
+            "{}",
+            self.world.render(|_, tile| match tile {
+                Some(tile) => tile.tile_type.to_char(),
+                None => ' ',
+            })
+        )
     }
 }
 
 pub fn test() {}
 
-pub fn part1() {
-    let input = import_lines("src/day13/input.txt");
-
-    let mut program = read(&input);
+pub fn solve1(input: &str) -> usize {
+    let mut program = read(input);
     execute(&mut program);
 
     let mut map = Map::new();
     map.world = Tile::read(&program.output);
 
-    println!(
-        "Number of block tiles on the screen is {}",
-        map
-            .world
-            .values()
-            .filter(|&tile| tile.tile_type == TileType::BLOCK)
-            .count()
-    );
+    map.world
+        .values()
+        .filter(|&tile| tile.tile_type == TileType::BLOCK)
+        .count()
 }
 
-pub fn part2() {
-    let input = import_lines("src/day13/input.txt");
+pub fn solve2(input: &str) -> CpuWidth {
+    play(&mut read(input), &mut NullRenderer)
+}
 
-    let mut program = read(&input);
+/// Plays the game until all blocks are broken and returns the final score, the screen is rendered every cycle
+fn play(program: &mut Program, renderer: &mut dyn Renderer) -> CpuWidth {
     program.set(0, 2); // Free play
 
     let mut map = Map::new();
 
-    print!("\x1B[2J");
-
     // Now play until finished
     while !program.is_finished() {
-        execute(&mut program);
+        execute(program);
 
         // Update the map with output of this program cycle and then clear the program input and output
         map.update(&program.output);
         program.flush();
 
-        // Print the map, because its fun
-        println!("\x1B[1;1H{}", map);
+        // Show the map, because its fun
+        renderer.draw(|| map.to_string());
 
         if program.is_waiting() {
             // Make sure bat moves towards ball by providing joystick input (nput is cleared at this point)
@@ -215,13 +191,29 @@ pub fn part2() {
                 program.add_input(0);
             }
         }
-
-        std::thread::sleep(time::Duration::from_millis(10));
     }
 
     // Update final state of map and score
     map.update(&program.output);
-    println!("\x1B[1;1H{}", map);
+    renderer.draw(|| map.to_string());
 
-    println!("Final score is {}", map.score);
+    map.score
+}
+
+pub fn part1() {
+    println!(
+        "Number of block tiles on the screen is {}",
+        solve1(&puzzle_input(13))
+    );
+}
+
+pub fn part2() {
+    let mut renderer = renderer();
+    let mut program = read(&puzzle_input(13));
+    session::record_from_env(&mut program);
+    let score = play(&mut program, &mut *renderer);
+    session::save_from_env(&program);
+    renderer.finish();
+
+    println!("Final score is {}", score);
 }
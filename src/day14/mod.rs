@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use crate::common::*;
 
-type ReactionStorage = HashMap<String, Reaction>;
+pub type ReactionStorage = HashMap<String, Reaction>;
 
 #[derive(Clone)]
 struct Chemical {
@@ -34,7 +34,7 @@ impl std::fmt::Display for Chemical {
 }
 
 #[derive(Clone)]
-struct Reaction {
+pub struct Reaction {
     target_chemical: Chemical,
     source_chemicals: Vec<Chemical>,
 }
@@ -72,17 +72,21 @@ impl Reaction {
     pub fn match_target_amount(self, amount: i64) -> Reaction {
         let mut result = self.to_owned();
 
-        while result.target_chemical.amount < amount {
-            result.target_chemical.amount += self.target_chemical.amount;
-            for (index, mut source) in result.source_chemicals.iter_mut().enumerate() {
-                source.amount += self.source_chemicals[index].amount;
-            }
+        // Number of times the reaction runs, at least once
+        let batches = std::cmp::max(
+            1,
+            (amount + self.target_chemical.amount - 1) / self.target_chemical.amount,
+        );
+
+        result.target_chemical.amount *= batches;
+        for source in result.source_chemicals.iter_mut() {
+            source.amount *= batches;
         }
         result
     }
 }
 
-fn parse_reactions(input: &str) -> ReactionStorage {
+pub fn parse_reactions(input: &str) -> ReactionStorage {
     input
         .split('\n')
         .map(|token| {
@@ -216,39 +220,43 @@ pub fn test() {
     println!("ORE required : {}", ore);
 }
 
-pub fn part1() {
-    let input = import_lines("src/day14/input.txt");
-    let ore = process(&input, 1);
-    println!("ORE required : {}", ore);
-    assert_eq!(ore, 892207);
+pub fn solve1(input: &str) -> i64 {
+    process(input, 1)
 }
 
-pub fn part2() {
-    let input = import_lines("src/day14/input.txt");
+/// Maximum amount of fuel that can be produced with the ore in the cargo hold
+pub fn solve2(input: &str) -> i64 {
+    const CARGO: i64 = 1000000000000;
 
-    let mut min_fuel_amount: i64 = 1935202; // Empirical, < needs 1 trillion ore
-    let mut max_fuel_amount: i64 = 1935330; // Empirical, > needs 1 trillion ore
+    // Leftovers make fuel cheaper in bulk, so the ore for a single fuel gives a lower bound
+    let mut min_fuel_amount: i64 = CARGO / process(input, 1);
+    let mut max_fuel_amount: i64 = min_fuel_amount * 2;
+    while process(input, max_fuel_amount) <= CARGO {
+        min_fuel_amount = max_fuel_amount;
+        max_fuel_amount *= 2;
+    }
 
-    loop {
+    // min_fuel_amount can be produced, max_fuel_amount can not
+    while max_fuel_amount - min_fuel_amount > 1 {
         let fuel_amount: i64 = (max_fuel_amount + min_fuel_amount) / 2;
 
-        let ore = process(&input, fuel_amount);
-
-        println!(
-            "ORE required for {} fuel : {}\t(min:{} max:{})",
-            fuel_amount, ore, min_fuel_amount, max_fuel_amount
-        );
-        if ore > 1000000000000 {
+        if process(input, fuel_amount) > CARGO {
             max_fuel_amount = fuel_amount;
         } else {
             min_fuel_amount = fuel_amount;
         }
+    }
 
-        if max_fuel_amount - min_fuel_amount <= 2 {
-            assert_eq!(ore, 999999660128);
-            assert_eq!(fuel_amount, 1935265);
+    min_fuel_amount
+}
 
-            break;
-        }
-    }
+pub fn part1() {
+    println!("ORE required : {}", solve1(&puzzle_input(14)));
+}
+
+pub fn part2() {
+    println!(
+        "Maximum amount of FUEL produced : {}",
+        solve2(&puzzle_input(14))
+    );
 }
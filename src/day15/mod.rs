@@ -1,11 +1,10 @@
-use std::slice::Iter;
-use std::{thread, time};
+use std::collections::VecDeque;
 
 use crate::common::*;
 use crate::cpu::*;
 
 extern crate pathfinding;
-use pathfinding::prelude::{absdiff, astar};
+use pathfinding::prelude::astar;
 
 #[derive(PartialEq, Clone)]
 enum DroidStatus {
@@ -25,218 +24,152 @@ impl From<i64> for DroidStatus {
     }
 }
 
-#[derive(PartialEq, Clone)]
-enum Move {
-    NORTH = 1,
-    SOUTH = 2,
-    WEST = 3,
-    EAST = 4,
+/// What the droid knows about a location
+#[derive(PartialEq, Clone, Copy, Debug)]
+enum Cell {
+    Unexplored,
+    Wall,
+    Open,
+    Oxygen,
 }
 
-impl Move {
-    pub fn iterator() -> Iter<'static, Move> {
-        static DIRECTIONS: [Move; 4] = [Move::NORTH, Move::SOUTH, Move::WEST, Move::EAST];
-        DIRECTIONS.iter()
+impl Cell {
+    fn is_reachable(&self) -> bool {
+        *self == Cell::Open || *self == Cell::Oxygen
     }
 }
 
-#[derive(PartialEq, Clone)]
-enum Movement {
-    START,
-    BLOCKED,
-    FREE,
-    PATH,
-    OXYGEN,
+struct World {
+    cells: Grid<Cell>,
+    start: Point,
+    oxygen: Option<Point>,
 }
 
-impl Movement {
-    fn is_reachable(&self) -> bool {
-        *self == Movement::START || *self == Movement::FREE || *self == Movement::OXYGEN
+impl World {
+    fn new() -> World {
+        let start = Point::new(0, 0);
+        let mut cells = Grid::new();
+        cells.insert(start, Cell::Open);
+
+        World {
+            cells,
+            start,
+            oxygen: None,
+        }
     }
-}
 
-#[derive(PartialEq, Clone)]
-struct Position {
-    position: Vector,
-    status: Movement,
-}
+    fn cell(&self, location: &Point) -> Cell {
+        self.cells
+            .get(location)
+            .cloned()
+            .unwrap_or(Cell::Unexplored)
+    }
+
+    fn set(&mut self, location: Point, cell: Cell) {
+        if cell == Cell::Oxygen {
+            self.oxygen = Some(location);
+        }
+        self.cells.insert(location, cell);
+    }
 
-type World = Vec<Position>;
+    /// Reachable locations next to given location
+    fn neighbors(&self, location: &Point) -> Vec<Point> {
+        self.cells
+            .neighbors(location, Adjacency::Four)
+            .filter(|(_, cell)| cell.is_reachable())
+            .map(|(location, _)| location)
+            .collect()
+    }
+}
 
 /// Tries to move in given direction and returns the droid movement status
-fn single_move(program: &mut Program, direction: &Move) -> DroidStatus {
-    program.add_input(direction.clone() as i64);
+fn single_move(program: &mut Program, heading: Heading) -> DroidStatus {
+    program.add_input(heading.droid_command());
     execute(program);
     let output = program.get_output(0).expect("Expected some output");
     program.flush();
     DroidStatus::from(output)
 }
 
-fn reverse_direction(moved: &Move) -> Move {
-    match moved {
-        Move::NORTH => Move::SOUTH,
-        Move::SOUTH => Move::NORTH,
-        Move::WEST => Move::EAST,
-        Move::EAST => Move::WEST,
-    }
-}
-
-fn target_position(position: &Vector, movement: &Move) -> Vector {
-    let mut result = position.clone();
-
-    match movement {
-        Move::NORTH => result.y += 1,
-        Move::SOUTH => result.y -= 1,
-        Move::WEST => result.x -= 1,
-        Move::EAST => result.x += 1,
-    }
-    result
-}
-
 /// Returns all neighbors of a location. Also indicates which move to take to get there and what type of location it is
-fn explore_neighbors(program: &mut Program, position: &Vector) -> Vec<(Move, Position)> {
-    let mut result = Vec::<(Move, Position)>::new();
+fn explore_neighbors(program: &mut Program, position: &Point) -> Vec<(Heading, Point, Cell)> {
+    let mut result = Vec::<(Heading, Point, Cell)>::new();
 
-    for this_move in Move::iterator() {
+    for heading in Heading::ALL.iter().cloned() {
         // Try to move
-        let status = match single_move(program, this_move) {
-            DroidStatus::MOVED => Movement::FREE,
-            DroidStatus::OXYGEN => Movement::OXYGEN,
-            _ => Movement::BLOCKED,
+        let cell = match single_move(program, heading) {
+            DroidStatus::MOVED => Cell::Open,
+            DroidStatus::OXYGEN => Cell::Oxygen,
+            _ => Cell::Wall,
         };
 
-        if status != Movement::BLOCKED {
+        if cell != Cell::Wall {
             // And move back
-            single_move(program, &reverse_direction(this_move));
+            single_move(program, heading.turn_around());
         }
 
-        // Store move, position and if reachable
-        result.push((
-            this_move.clone(),
-            Position {
-                position: target_position(position, this_move),
-                status: status,
-            },
-        ));
+        // Store move, position and what is there
+        result.push((heading, heading.step(position, YAxis::Up), cell));
     }
 
     result
 }
 
-/// Returns extends (bottom left, top right) of world
-fn world_extends(world: &World) -> (Vector, Vector) {
-    let x_min = world
-        .iter()
-        .min_by(|x, y| x.position.x.cmp(&y.position.x))
-        .unwrap()
-        .position
-        .x;
-    let x_max = world
-        .iter()
-        .max_by(|x, y| x.position.x.cmp(&y.position.x))
-        .unwrap()
-        .position
-        .x;
-    let y_min = world
-        .iter()
-        .min_by(|x, y| x.position.y.cmp(&y.position.y))
-        .unwrap()
-        .position
-        .y;
-    let y_max = world
-        .iter()
-        .max_by(|x, y| x.position.y.cmp(&y.position.y))
-        .unwrap()
-        .position
-        .y;
-
-    (
-        Vector {
-            x: x_min,
-            y: y_min,
-            z: 0,
-        },
-        Vector {
-            x: x_max,
-            y: y_max,
-            z: 0,
-        },
-    )
-}
-
-fn world_contains<'a>(world: &'a World, location: &Vector) -> Option<&'a Position> {
-    world
-        .iter()
-        .find(|p| p.position.x == location.x && p.position.y == location.y)
-}
-
-fn world_contains_mut<'a>(world: &'a mut World, location: &Vector) -> Option<&'a mut Position> {
-    world
-        .iter_mut()
-        .find(|p| p.position.x == location.x && p.position.y == location.y)
-}
-
-fn world_print(world: &World) {
-    let (bl, tr) = world_extends(world);
+/// The world as text with the path on it, north at the top
+fn world_render(world: &World, path: &[Point]) -> String {
+    let (bl, tr) = world.cells.extends().expect("Empty world");
 
+    let mut frame = String::new();
     for y in (bl.y..tr.y + 1).rev() {
         for x in bl.x..tr.x + 1 {
-            let movement = if x == 0 && y == 0 {
-                Movement::START
-            } else if let Some(p) = world_contains(world, &Vector { x: x, y: y, z: 0 }) {
-                p.status.clone()
+            let location = Point::new(x, y);
+
+            frame.push(if location == world.start {
+                '#'
+            } else if path.contains(&location) {
+                '.'
             } else {
-                Movement::BLOCKED
-            };
-
-            print!(
-                "{}",
-                match movement {
-                    Movement::START => '#',
-                    Movement::PATH => '.',
-                    Movement::FREE => ' ',
-                    Movement::OXYGEN => '*',
+                match world.cell(&location) {
+                    Cell::Open => ' ',
+                    Cell::Oxygen => '*',
                     _ => '+',
                 }
-            );
+            });
         }
-        println!();
+        frame.push('\n');
     }
+    frame
 }
 
 /// Build the world by examining valid moves
-/// This is a DFS algorithm
-fn world_build(program: &mut Program) -> World {
+/// This is a DFS algorithm, every step the droid takes is rendered
+fn world_build(program: &mut Program, renderer: &mut dyn Renderer) -> World {
     let mut world = World::new();
 
-    let start = Position {
-        position: Vector { x: 0, y: 0, z: 0 },
-        status: Movement::START,
-    };
-
-    // Add initial position to world
-    world.push(start.clone());
-
     // DFS backtrace stack
-    let mut dfs = Vec::<(Move, Position)>::new();
-    dfs.push((Move::NORTH, start));
+    let mut dfs = Vec::<(Heading, Point)>::new();
+    dfs.push((Heading::North, world.start));
 
-    while dfs.len() > 0 {
-        let waypoint = dfs.last().expect("Not good").clone();
+    while !dfs.is_empty() {
+        let waypoint = *dfs.last().expect("Not good");
 
-        let neighbors = explore_neighbors(program, &waypoint.1.position);
+        let neighbors = explore_neighbors(program, &waypoint.1);
+        renderer.draw(|| {
+            let trail = dfs.iter().map(|(_, l)| *l).collect::<Vec<Point>>();
+            world_render(&world, &trail)
+        });
 
         let mut moved = false;
-        for neighbor in neighbors {
-            if world_contains(&mut world, &neighbor.1.position) == None {
+        for (heading, location, cell) in neighbors {
+            if world.cell(&location) == Cell::Unexplored {
                 // Add it to the world
-                world.push(neighbor.1.clone());
+                world.set(location, cell);
 
-                if neighbor.1.status.is_reachable() {
+                if cell.is_reachable() {
                     // Location is reachable. move to it and explore further
-                    single_move(program, &neighbor.0);
+                    single_move(program, heading);
 
-                    dfs.push(neighbor);
+                    dfs.push((heading, location));
 
                     moved = true;
                     break;
@@ -246,7 +179,7 @@ fn world_build(program: &mut Program) -> World {
 
         // End of the line, move back to previous position
         if !moved {
-            single_move(program, &reverse_direction(&waypoint.0));
+            single_move(program, waypoint.0.turn_around());
             // And remove the dead end
             dfs.pop();
         }
@@ -254,127 +187,229 @@ fn world_build(program: &mut Program) -> World {
     world
 }
 
-/// Cannot move diagonally
-fn is_adjacent(a: &Vector, b: &Vector) -> bool {
-    ((a.x - b.x).abs() <= 1 && (a.y - b.y).abs() == 0)
-        || ((a.y - b.y).abs() <= 1 && (a.x - b.x).abs() == 0)
+/// Manhattan distance between positions
+fn distance(a: &Point, b: &Point) -> u32 {
+    a.manhattan(b) as u32
+}
+
+/// Result of filling the world with oxygen
+struct Flood {
+    /// Minutes until all reachable locations are filled
+    minutes: u32,
+    /// Minute at which each reachable location is filled
+    fill_time: Grid<u32>,
 }
 
-/// Find valid neighbors for given position
-fn neighbors<'a>(world: &'a World, position: &Vector) -> Vec<&'a Position> {
-    world
-        .iter()
-        .filter_map(|p| {
-            if p.status != Movement::BLOCKED && is_adjacent(&p.position, position) {
-                Some(p)
-            } else {
-                None
+/// Fills the world with oxygen from the oxygen system, a breadth first search where each level is one minute
+fn oxygen_flood(world: &World) -> Flood {
+    let source = world.oxygen.expect("Badness, no oxygen system found");
+
+    let mut fill_time = Grid::new();
+    fill_time.insert(source, 0);
+
+    let mut minutes = 0;
+    let mut queue = VecDeque::new();
+    queue.push_back((source, 0));
+
+    while let Some((location, minute)) = queue.pop_front() {
+        minutes = minute;
+
+        for neighbor in world.neighbors(&location) {
+            if !fill_time.contains(&neighbor) {
+                fill_time.insert(neighbor, minute + 1);
+                queue.push_back((neighbor, minute + 1));
             }
-        })
-        .collect()
-}
+        }
+    }
 
-/// Manhattan distance between positions
-fn distance(a: &Vector, b: &Vector) -> u32 {
-    (absdiff(a.x, b.x) + absdiff(a.y, b.y)) as u32
+    Flood { minutes, fill_time }
 }
 
 pub fn test() {}
 
-pub fn part1() {
-    let mut world = world_build(&mut read(&import_lines("src/day15/input.txt")));
-    let start = world
-        .iter()
-        .find(|&s| s.status == Movement::START)
-        .expect("Badness, no start found")
-        .clone();
-    let end = world
-        .iter()
-        .find(|&s| s.status == Movement::OXYGEN)
-        .expect("Badness, no end found")
-        .clone();
-
-    world_print(&world);
-    println!("Oxygen found at {}", end.position);
-
-    let path = astar(
-        &start.position,
+/// Shortest path from the start to the oxygen system and its number of moves
+fn shortest_path(world: &World) -> (Vec<Point>, u32) {
+    let end = world.oxygen.expect("Badness, no end found");
+
+    astar(
+        &world.start,
         |p| {
-            neighbors(&world, p)
-                .iter()
-                .map(|p| (p.position.clone(), 1))
-                .collect::<Vec<(Vector, u32)>>()
+            world
+                .neighbors(p)
+                .into_iter()
+                .map(|p| (p, 1))
+                .collect::<Vec<(Point, u32)>>()
         },
-        |p| distance(p, &end.position),
-        |p| *p == end.position,
+        |p| distance(p, &end),
+        |p| *p == end,
     )
-    .expect("No path found");
+    .expect("No path found")
+}
 
-    // Mark the path found
-    for waypoint in &path.0 {
-        if waypoint != &end.position {
-            if let Some(p) = world_contains_mut(&mut world, waypoint) {
-                p.status = Movement::PATH
-            }
-        }
-        world_print(&world);
-        thread::sleep(time::Duration::from_millis(50));
+pub fn solve1(input: &str) -> u32 {
+    let world = world_build(&mut read(input), &mut NullRenderer);
+    shortest_path(&world).1
+}
+
+pub fn solve2(input: &str) -> u32 {
+    let world = world_build(&mut read(input), &mut NullRenderer);
+    oxygen_flood(&world).minutes
+}
+
+pub fn part1() {
+    let mut renderer = renderer();
+    let mut program = read(&puzzle_input(15));
+    session::record_from_env(&mut program);
+    let world = world_build(&mut program, &mut *renderer);
+    session::save_from_env(&program);
+
+    println!(
+        "Oxygen found at {}",
+        world.oxygen.expect("Badness, no end found")
+    );
+
+    let path = shortest_path(&world);
+
+    // Show the path found
+    for step in 1..path.0.len() {
+        renderer.draw(|| world_render(&world, &path.0[..step]));
     }
+    renderer.finish();
 
     println!("Number of moves: {}", path.1);
 }
 
 pub fn part2() {
-    let mut world = world_build(&mut read(&import_lines("src/day15/input.txt")));
+    let mut renderer = renderer();
+    let mut program = read(&puzzle_input(15));
+    session::record_from_env(&mut program);
+    let mut world = world_build(&mut program, &mut *renderer);
+    session::save_from_env(&program);
+    let flood = oxygen_flood(&world);
+
+    // Replay the flood minute by minute
+    for minute in 1..=flood.minutes {
+        for (location, time) in flood.fill_time.iter() {
+            if *time == minute {
+                world.cells.insert(location, Cell::Oxygen);
+            }
+        }
 
-    let mut minutes = 0;
-    loop {
-        // All locations that are not already oxygen
-        let not_oxygen = world
+        renderer.draw(|| world_render(&world, &[]));
+    }
+    renderer.finish();
+
+    println!(
+        "It took {} minutes for oxygen to fill all reachable locations",
+        flood.minutes
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    /// World of the given cells only, without the open start that the droid begins on
+    fn world(cells: impl IntoIterator<Item = (Point, Cell)>) -> World {
+        let mut world = World::new();
+        world.cells = Grid::new();
+        for (location, cell) in cells {
+            world.set(location, cell);
+        }
+        world
+    }
+
+    /// Builds a world without the droid: a square of open cells of given size with walls on every other row and column
+    /// crossing, like the maze the droid explores
+    fn synthetic_world(size: i32) -> World {
+        let mut world = World::new();
+        for y in -1..=size {
+            for x in -1..=size {
+                let wall = x < 0 || y < 0 || x == size || y == size || (x % 2 == 1 && y % 2 == 1);
+                world.set(Point::new(x, y), if wall { Cell::Wall } else { Cell::Open });
+            }
+        }
+        world.set(Point::new(size - 1, size - 1), Cell::Oxygen);
+        world
+    }
+
+    /// The flood as it was done before the world was indexed: a linear list of positions that is scanned
+    /// completely for every location, every minute
+    fn oxygen_flood_linear(world: &World) -> u32 {
+        let mut cells = world
+            .cells
             .iter()
-            .filter(|p| p.status.is_reachable() && p.status != Movement::OXYGEN)
-            .collect::<Vec<&Position>>();
-
-        let mut new_oxygen = Vec::<Vector>::new();
-
-        //  We're done if all positions are filled with oxygen
-        if not_oxygen.len() == 0 {
-            break;
-        } else {
-            // See if any of them have a neighbor filled with oxygen
-            for p in not_oxygen {
-                for neighbor in neighbors(&world, &p.position) {
-                    if world
+            .map(|(l, c)| (l, *c))
+            .collect::<Vec<(Point, Cell)>>();
+        let is_adjacent = |a: &Point, b: &Point| a.manhattan(b) == 1;
+
+        let mut minutes = 0;
+        loop {
+            let new_oxygen = cells
+                .iter()
+                .filter(|(_, c)| *c == Cell::Open)
+                .filter(|(l, _)| {
+                    cells
                         .iter()
-                        .any(|p| p.position == neighbor.position && p.status == Movement::OXYGEN)
-                    {
-                        // If so, they are going to be filled with oxygen this minute
-                        new_oxygen.push(p.position.clone());
-
-                        // Any neighbor suffices
-                        break;
-                    }
-                }
+                        .any(|(n, c)| *c == Cell::Oxygen && is_adjacent(l, n))
+                })
+                .map(|(l, _)| *l)
+                .collect::<Vec<Point>>();
+
+            if new_oxygen.is_empty() {
+                break;
             }
-        }
 
-        // Mark all new oxygen positions
-        for position in new_oxygen {
-            world
-                .iter_mut()
-                .find(|p| p.position == position)
-                .unwrap()
-                .status = Movement::OXYGEN;
+            for location in new_oxygen {
+                cells.iter_mut().find(|(l, _)| *l == location).unwrap().1 = Cell::Oxygen;
+            }
+            minutes += 1;
         }
+        minutes
+    }
 
-        minutes += 1;
-
-        world_print(&world);
-        thread::sleep(time::Duration::from_millis(50));
+    /// Compares the breadth first oxygen flood with the linear scan it replaced, run with --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn benchmark() {
+        for size in [11, 21, 41].iter() {
+            let world = synthetic_world(*size);
+
+            let start = Instant::now();
+            let flood = oxygen_flood(&world);
+            let indexed = start.elapsed();
+
+            let start = Instant::now();
+            let minutes = oxygen_flood_linear(&world);
+            let linear = start.elapsed();
+
+            assert_eq!(flood.minutes, minutes);
+            println!(
+                "{:2}x{:<2} {} minutes, indexed BFS {:?}, linear scan {:?}",
+                size, size, minutes, indexed, linear
+            );
+        }
     }
 
-    println!(
-        "It took {} minutes for oxygen to fill all reachable locations",
-        minutes
-    );
+    #[test]
+    fn oxygen_flood_example() {
+        // Example from the puzzle, O is the oxygen system
+        let map = " ##   \n#..## \n#.#..#\n#.O.# \n ###  ";
+        let world = world(Grid::parse(map, |c| match c {
+            '#' => Some(Cell::Wall),
+            '.' => Some(Cell::Open),
+            'O' => Some(Cell::Oxygen),
+            _ => None,
+        }));
+        assert_eq!(world.cell(&Point::new(0, 0)), Cell::Unexplored);
+
+        let flood = oxygen_flood(&world);
+        assert_eq!(flood.minutes, 4);
+        assert_eq!(flood.fill_time.get(&Point::new(2, 3)), Some(&0));
+        assert_eq!(flood.fill_time.get(&Point::new(1, 1)), Some(&3));
+        assert_eq!(flood.fill_time.get(&Point::new(4, 2)), Some(&3));
+        assert_eq!(flood.fill_time.get(&Point::new(2, 1)), Some(&4));
+        assert_eq!(oxygen_flood_linear(&world), 4);
+    }
 }
@@ -43,7 +43,7 @@ fn fft(input: &Vec<i32>, phases: i32) -> Vec<i32> {
     output
 }
 
-fn parse_input(input: &str) -> Vec<i32> {
+pub fn parse_input(input: &str) -> Vec<i32> {
     input
         .chars()
         .map(|c| i32::from_str_radix(&c.to_string(), 10).expect("Invalid input"))
@@ -67,15 +67,18 @@ pub fn test() {
     }
 }
 
+/// First eight digits of the output after 100 phases
+pub fn solve1(input: &str) -> String {
+    let result = fft(&parse_input(input), 100);
+    result[0..8].iter().map(|d| d.to_string()).collect()
+}
+
 pub fn part1() {
-    let input = import_lines("src/day16/input.txt");
-    let result = fft(&parse_input(&input), 100);
-    println!("Part 1 : {:?}", &result[0..8]);
-    assert_eq!(&result[0..8], [5, 3, 2, 9, 6, 0, 8, 2]);
+    println!("Part 1 : {}", solve1(&puzzle_input(16)));
 }
 
 pub fn part2() {
-    // let input = import_lines("src/day16/input.txt").repeat(10000);
+    // let input = puzzle_input(16).repeat(10000);
     // let offset = parse_offset(&input) as usize;
 
     // println!("{}", offset);
@@ -1,5 +1,7 @@
-fn input() -> &'static str {
-    "1,0,0,3,1,1,2,3,1,3,4,3,1,5,0,3,2,1,9,19,1,10,19,23,2,9,23,27,1,6,27,31,2,31,9,35,1,5,35,39,1,10,39,43,1,10,43,47,2,13,47,51,1,10,51,55,2,55,10,59,1,9,59,63,2,6,63,67,1,5,67,71,1,71,5,75,1,5,75,79,2,79,13,83,1,83,5,87,2,6,87,91,1,5,91,95,1,95,9,99,1,99,6,103,1,103,13,107,1,107,5,111,2,111,13,115,1,115,6,119,1,6,119,123,2,123,13,127,1,10,127,131,1,131,2,135,1,135,5,0,99,2,14,0,0"
+use crate::common::puzzle_input;
+
+fn input() -> String {
+    puzzle_input(2)
 }
 
 fn execute(mut program: Vec<usize>) -> usize {
@@ -39,29 +41,35 @@ pub fn test() {
     execute(program);
 }
 
-pub fn part1() {
-    let mut program = read(input());
-    program[1] = 12;
-    program[2] = 2;
-    execute(program);
+fn run(input: &str, noun: usize, verb: usize) -> usize {
+    let mut program = read(input);
+    program[1] = noun;
+    program[2] = verb;
+    execute(program)
 }
 
-pub fn part2() {
+pub fn solve1(input: &str) -> usize {
+    run(input, 12, 2)
+}
+
+pub fn solve2(input: &str) -> Option<usize> {
     for verb in 0..100 {
         for noun in 0..100 {
-            let mut program = read(input());
-            program[1] = noun;
-            program[2] = verb;
-            let result = execute(program);
-            if result == 19690720 {
-                println!(
-                    "noun: {} verb: {} result: {}",
-                    noun,
-                    verb,
-                    100 * noun + verb
-                );
-                break;
+            if run(input, noun, verb) == 19690720 {
+                return Some(100 * noun + verb);
             }
         }
     }
+    None
+}
+
+pub fn part1() {
+    println!("Value at position 0: {}", solve1(&input()));
+}
+
+pub fn part2() {
+    match solve2(&input()) {
+        Some(answer) => println!("noun: {} verb: {} result: {}", answer / 100, answer % 100, answer),
+        None => println!("No noun and verb produce 19690720"),
+    }
 }
@@ -1,182 +1,195 @@
-use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
 use std::str::FromStr;
 
-fn wire1() -> &'static str {
-    "R1010,D422,L354,U494,L686,U894,R212,U777,L216,U9,L374,U77,R947,U385,L170,U916,R492,D553,L992,D890,L531,U360,R128,U653,L362,U522,R817,U198,L126,D629,L569,U300,L241,U145,R889,D196,L450,D576,L319,D147,R985,U889,L941,U837,L608,D77,L864,U911,L270,D869,R771,U132,L249,U603,L36,D328,L597,U992,L733,D370,L947,D595,L308,U536,L145,U318,R55,D773,R175,D505,R483,D13,R780,U778,R445,D107,R490,U245,L587,U502,R446,U639,R150,U35,L455,D522,R866,U858,R394,D975,R513,D378,R58,D646,L374,D675,R209,U228,R530,U543,L480,U677,L912,D164,L573,U587,L784,D626,L994,U250,L215,U985,R684,D79,L877,U811,L766,U617,L665,D246,L408,U800,L360,D272,L436,U138,R240,U735,L681,U68,L608,D59,R532,D808,L104,U968,R887,U819,R346,U698,L317,U582,R516,U55,L303,U607,L457,U479,L510,D366,L583,U519,R878,D195,R970,D267,R842,U784,R9,D946,R833,D238,L232,D94,L860,D47,L346,U951,R491,D745,R849,U273,R263,U392,L341,D808,R696,U326,R886,D296,L865,U833,R241,U644,R729,D216,R661,D712,L466,D699,L738,U5,L556,D693,R912,D13,R48,U63,L877,U628,L689,D929,R74,U924,R612,U153,R417,U425,L879,D378,R79,D248,L3,U519,R366,U281,R439,D823,R149,D668,R326,D342,L213,D735,R504,U265,L718,D842,L565,U105,L214,U963,R518,D681,R642,U170,L111,U6,R697,U572,R18,U331,L618,D255,R534,D322,L399,U595,L246,U651,L836,U757,R417,D795,R291,U759,L568,U965,R828,D570,R350,U317,R338,D173,L74,D833,L650,D844,L70,U913,R594,U407,R674,D684,L481,D564,L128,D277,R851,D274,L435,D582,R469,U729,R387,D818,R443,U504,R414,U8,L842,U845,R275,U986,R53,U660,R661,D225,R614,U159,R477"
+use crate::common::{puzzle_input, read_file, InputError, Point};
+
+pub mod segments;
+
+/// Invalid move in the path of a wire, tokens are the moves between the commas counted from 0
+#[derive(Debug, PartialEq)]
+pub struct PathError {
+    pub token: usize,
+    pub text: String,
+    pub reason: &'static str,
 }
 
-fn wire2() -> &'static str {
-    "L1010,D698,R442,U660,L719,U702,L456,D86,R938,D177,L835,D639,R166,D285,L694,U468,L569,D104,L234,D574,L669,U299,L124,D275,L179,D519,R617,U72,L985,D248,R257,D276,L759,D834,R490,U864,L406,U181,R911,U873,R261,D864,R260,U759,R648,U158,R308,D386,L835,D27,L745,U91,R840,U707,R275,U543,L663,U736,L617,D699,R924,U103,R225,U455,R708,U319,R569,U38,R315,D432,L179,D975,R519,D546,L295,U680,L685,U603,R262,D250,R7,U171,R261,U519,L832,U534,L471,U431,L474,U886,R10,D179,L79,D555,R452,U452,L832,U863,L367,U538,L237,D160,R441,U605,R942,U259,L811,D552,R646,D353,L225,D94,L35,D307,R752,U23,R698,U610,L379,D932,R698,D751,R178,D347,R325,D156,R471,D555,R558,D593,R773,U2,L955,U764,L735,U438,R364,D640,L757,U534,R919,U409,R361,U407,R336,D808,R877,D648,R610,U198,R340,U94,R795,D667,R811,U975,L965,D224,R565,D681,L64,U567,R621,U922,L665,U329,R242,U592,L727,D481,L339,U402,R213,D280,R656,U169,R976,D962,L294,D505,L251,D689,L497,U133,R230,D441,L90,D220,L896,D657,L500,U331,R502,U723,R762,D613,L447,D256,L226,U309,L935,U384,L740,D459,R309,D707,R952,D747,L304,D105,R977,D539,R941,D21,R291,U216,R132,D543,R515,U453,L854,D42,R982,U102,L469,D639,R559,D68,R302,U734,R980,D214,R107,D191,L730,D793,L63,U17,R807,U196,R412,D592,R330,D941,L87,D291,L44,D94,L272,D780,R968,U837,L712,D704,R163,U981,R537,U778,R220,D303,L196,D951,R163,D446,R11,D623,L72,D778,L158,U660,L189,D510,L247,D716,L89,U887,L115,U114,L36,U81,R927,U293,L265,U183,R331,D267,R745,D298,L561,D918,R299,U810,L322,U679,L739,D854,L581,U34,L862,D779,R23"
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "token {} {:?}: {}", self.token, self.text, self.reason)
+    }
 }
 
+impl Error for PathError {}
+
 struct Move {
-    m: char,
+    step: Point,
     c: i32,
 }
 
 impl FromStr for Move {
-    type Err = std::num::ParseIntError;
+    type Err = &'static str;
 
     fn from_str(source: &str) -> Result<Self, Self::Err> {
-        let m: char = source.chars().nth(0).unwrap();
-        let c: i32 = i32::from_str(&source[1..])?;
-
-        Ok(Move { m, c })
+        let mut chars = source.chars();
+        let step = match chars.next() {
+            Some('U') => Point::new(0, 1),
+            Some('R') => Point::new(1, 0),
+            Some('D') => Point::new(0, -1),
+            Some('L') => Point::new(-1, 0),
+            Some(_) => return Err("unknown direction, expected U, R, D or L"),
+            None => return Err("empty move"),
+        };
+
+        match chars.as_str().parse::<i32>() {
+            Ok(c) if c >= 0 => Ok(Move { step, c }),
+            _ => Err("invalid length"),
+        }
     }
 }
 
-#[derive(Clone)]
-struct Coordinate {
-    x: i32,
-    y: i32,
-
-    total: i32,
+fn read_moves(input: &str) -> Result<Vec<Move>, PathError> {
+    input
+        .split(',')
+        .map(str::trim)
+        .enumerate()
+        .map(|(token, text)| {
+            text.parse().map_err(|reason| PathError {
+                token,
+                text: text.to_string(),
+                reason,
+            })
+        })
+        .collect()
 }
 
-impl Coordinate {
-    pub fn manhattan(&self) -> i32 {
-        self.x.abs() + self.y.abs()
+pub fn test() {
+    let wires = wires(
+        "R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51\n\
+         U98,R91,D20,R16,D67,R40,U7,R15,U6,R7",
+    );
+
+    for c in segments::crossings(&wires) {
+        println!("{} Steps:{} --> {}", c.position, c.steps, c.distance());
     }
+    println!("{}", segments::render(&wires));
 }
 
-impl PartialEq for Coordinate {
-    fn eq(&self, other: &Self) -> bool {
-        self.x == other.x && self.y == other.y
-    }
+/*
+	All wires in the input, one per line
+*/
+pub fn wires(input: &str) -> Vec<segments::Wire> {
+    parse_wires(input).unwrap_or_else(|(line, e)| panic!("Invalid wire on line {}, {}", line, e))
 }
 
-impl Eq for Coordinate {}
+/*
+	Wires one per line, an error has the number of the line counted from 1
+*/
+fn parse_wires(input: &str) -> Result<Vec<segments::Wire>, (usize, PathError)> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(ix, line)| line.trim().parse().map_err(|e| (ix + 1, e)))
+        .collect()
+}
 
-impl Ord for Coordinate {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.manhattan().cmp(&other.manhattan())
-    }
+/*
+	Wires in a file, one per line
+*/
+pub fn read_wires(path: &Path) -> Result<Vec<segments::Wire>, InputError> {
+    parse_wires(&read_file(path)?).map_err(|(line, e)| InputError::Parse {
+        path: path.to_path_buf(),
+        line,
+        message: e.to_string(),
+    })
 }
 
-impl PartialOrd for Coordinate {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
+pub fn solve1(input: &str) -> Option<i32> {
+    segments::crossings(&wires(input))
+        .first()
+        .map(|c| c.distance())
 }
 
-impl std::fmt::Display for Coordinate {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "({},{}) Steps:{}", self.x, self.y, self.total)
-    }
+/*
+	Fewest combined steps of two wires to a point where they cross, of all pairs of wires. The steps of a wire to a
+	point are those of its first visit, the central port does not count
+*/
+pub fn min_signal_delay(wires: &[segments::Wire]) -> Option<i32> {
+    segments::crossings(wires).iter().map(|c| c.steps).min()
 }
 
-fn read_moves(input: &str) -> Vec<Move> {
-    input
-        .split(",")
-        .map(|s| Move::from_str(s).unwrap())
-        .collect()
+pub fn solve2(input: &str) -> Option<i32> {
+    min_signal_delay(&wires(input))
 }
 
-fn calculate_coordinates(moves: &Vec<Move>) -> Vec<Coordinate> {
-    let mut coordinates = Vec::<Coordinate>::new();
-
-    let mut pos = Coordinate {
-        x: 0,
-        y: 0,
-        total: 0,
-    };
-    for m in moves {
-        match m.m {
-            'U' => {
-                for _ in 0..m.c {
-                    pos.y += 1;
-                    pos.total += 1;
-                    coordinates.push(pos.clone());
-                }
-            }
-            'R' => {
-                for _ in 0..m.c {
-                    pos.x += 1;
-                    pos.total += 1;
-                    coordinates.push(pos.clone());
-                }
-            }
-            'D' => {
-                for _ in 0..m.c {
-                    pos.y -= 1;
-                    pos.total += 1;
-                    coordinates.push(pos.clone());
-                }
-            }
-            'L' => {
-                for _ in 0..m.c {
-                    pos.x -= 1;
-                    pos.total += 1;
-                    coordinates.push(pos.clone());
-                }
-            }
-            _ => {}
-        }
+pub fn part1() {
+    match solve1(&puzzle_input(3)) {
+        Some(distance) => println!("Closest intersection at distance {}", distance),
+        None => println!("The wires do not cross"),
     }
-    coordinates
 }
 
-pub fn test() {
-    let wire1 = "R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51";
-    let wire2 = "U98,R91,D20,R16,D67,R40,U7,R15,U6,R7";
-
-    let moves = read_moves(wire1);
-    let c1 = calculate_coordinates(&moves);
-
-    let moves = read_moves(wire2);
-    let c2 = calculate_coordinates(&moves);
-
-    let mut duplicates: Vec<&Coordinate> = c1.iter().filter(|c| c2.contains(c)).collect();
-    duplicates.sort_by(|a, b| a.manhattan().cmp(&b.manhattan()));
-    for c in duplicates {
-        println!("{} --> {}", c, c.manhattan());
+pub fn part2() {
+    match solve2(&puzzle_input(3)) {
+        Some(steps) => println!("Fewest combined steps to an intersection {}", steps),
+        None => println!("The wires do not cross"),
     }
 }
 
-pub fn part1() {
-    let moves = read_moves(wire1());
-    let mut c1 = calculate_coordinates(&moves);
-    c1.sort_by(|a, b| a.manhattan().cmp(&b.manhattan()));
-    c1.dedup();
-
-    let moves = read_moves(wire2());
-    let mut c2 = calculate_coordinates(&moves);
-    c2.sort_by(|a, b| a.manhattan().cmp(&b.manhattan()));
-    c2.dedup();
-
-    for c in &c1 {
-        if c2.contains(&c) {
-            println!("{} --> {}", c, c.manhattan());
-
-            break;
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strict_paths() {
+        assert_eq!(read_moves("R8,U5 , L5,D3").map(|m| m.len()), Ok(4));
+
+        let error = |path| read_moves(path).err().map(|e| (e.token, e.text, e.reason));
+        assert_eq!(
+            error("R8,U5,X5,D3"),
+            Some((
+                2,
+                "X5".to_string(),
+                "unknown direction, expected U, R, D or L"
+            ))
+        );
+        assert_eq!(error("R8,,D3"), Some((1, "".to_string(), "empty move")));
+        assert_eq!(
+            error("R8,U-5"),
+            Some((1, "U-5".to_string(), "invalid length"))
+        );
+        assert_eq!(error("R8,U"), Some((1, "U".to_string(), "invalid length")));
+
+        assert_eq!(parse_wires("R8,U5\n\nU7,R6").map(|w| w.len()), Ok(2));
+        assert_eq!(parse_wires("R8,U5\n\nU7,6R").unwrap_err().0, 3);
     }
-}
 
-pub fn part2() {
-    let moves = read_moves(wire1());
-    let mut c1 = calculate_coordinates(&moves);
-    c1.sort_by(|a, b| a.manhattan().cmp(&b.manhattan()));
-    c1.dedup();
-
-    let moves = read_moves(wire2());
-    let mut c2 = calculate_coordinates(&moves);
-    c2.sort_by(|a, b| a.manhattan().cmp(&b.manhattan()));
-    c2.dedup();
-
-    let mut total = 0;
-    for primary in &c1 {
-        match c2.iter().find(|&x| x == primary) {
-            Some(secondary) => {
-                if primary.total + secondary.total < total || total == 0 {
-                    total = primary.total + secondary.total;
-                    println!("{} + {} ({})", primary, secondary, total);
-                } else {
-                    println!("exit");
-                    break;
-                }
-            }
-            _ => {}
-        }
+    #[test]
+    fn signal_delay() {
+        let delay = |input| min_signal_delay(&wires(input));
+
+        assert_eq!(delay("R8,U5,L5,D3\nU7,R6,D4,L4"), Some(30));
+        assert_eq!(
+            delay("R75,D30,R83,U83,L12,D49,R71,U7,L72\nU62,R66,U55,R34,D71,R55,D58,R83"),
+            Some(610)
+        );
+        assert_eq!(
+            delay(
+                "R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51\n\
+                 U98,R91,D20,R16,D67,R40,U7,R15,U6,R7"
+            ),
+            Some(410)
+        );
+        assert_eq!(delay("R8,U5\nL8,D5"), None);
+
+        // The first wire passes (3,0) after 3 and after 11 steps, the second after 5 steps
+        let looped = wires("R5,U2,L2,D4\nD1,R3,U1");
+        assert_eq!(looped[0].first_visits()[&Point::new(3, 0)], 3);
+        assert_eq!(min_signal_delay(&looped), Some(8));
+
+        // The fewest steps of any pair, here the second and third wire meet one step from the port
+        assert_eq!(delay("R8,U5\nU7,R6,D4,L4\nU1,R1"), Some(2));
     }
 }
@@ -0,0 +1,368 @@
+//! Wires as horizontal and vertical segments
+//!
+//! Every segment of a wire remembers how many steps along the wire its start is, so the steps to any point on it
+//! follow without walking the grid. Crossings between any number of wires are found with a sweep over x: horizontal
+//! segments are active between their ends and every vertical segment looks up the active ones in its range of y.
+//! Segments of different wires on the same line cross in every point they share.
+
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+
+use super::{read_moves, PathError};
+use crate::common::{Grid, Point};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Segment {
+    pub start: Point,
+    pub end: Point,
+    /// Steps along the wire to the start
+    pub steps: i32,
+}
+
+impl Segment {
+    pub fn is_horizontal(&self) -> bool {
+        self.start.y == self.end.y
+    }
+
+    /// Corner with the lowest x and y
+    fn min(&self) -> Point {
+        Point::new(self.start.x.min(self.end.x), self.start.y.min(self.end.y))
+    }
+
+    /// Corner with the highest x and y
+    fn max(&self) -> Point {
+        Point::new(self.start.x.max(self.end.x), self.start.y.max(self.end.y))
+    }
+
+    /// Steps along the wire to a point on the segment
+    pub fn steps_to(&self, point: Point) -> i32 {
+        self.steps + self.start.manhattan(&point)
+    }
+}
+
+/// Wire starting at the central port
+#[derive(Clone, Debug, PartialEq)]
+pub struct Wire {
+    pub segments: Vec<Segment>,
+}
+
+impl FromStr for Wire {
+    type Err = PathError;
+
+    fn from_str(path: &str) -> Result<Wire, PathError> {
+        let mut segments = Vec::new();
+        let mut start = Point::new(0, 0);
+        let mut steps = 0;
+
+        for m in read_moves(path)? {
+            if m.c == 0 {
+                continue;
+            }
+
+            let end = start + m.step * m.c;
+            segments.push(Segment { start, end, steps });
+            start = end;
+            steps += m.c;
+        }
+
+        Ok(Wire { segments })
+    }
+}
+
+impl Wire {
+    /// Steps to every point the wire visits after leaving the central port, the first time it gets there. Walks every
+    /// step, so it only serves to check the crossings in tests
+    #[cfg(test)]
+    pub fn first_visits(&self) -> HashMap<Point, i32> {
+        let mut visits = HashMap::new();
+        for segment in self.segments.iter() {
+            let offset = segment.end - segment.start;
+            let step = Point::new(offset.x.signum(), offset.y.signum());
+            for ix in 1..=offset.manhattan_length() {
+                visits
+                    .entry(segment.start + step * ix)
+                    .or_insert(segment.steps + ix);
+            }
+        }
+        visits.remove(&Point::new(0, 0));
+        visits
+    }
+}
+
+/// Point where two different wires meet
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Crossing {
+    pub position: Point,
+    /// Indexes of the wires, the lowest first
+    pub wires: (usize, usize),
+    /// Steps along both wires to the crossing
+    pub steps: i32,
+}
+
+impl Crossing {
+    fn new(position: Point, a: (usize, &Segment), b: (usize, &Segment)) -> Crossing {
+        Crossing {
+            position,
+            wires: (a.0.min(b.0), a.0.max(b.0)),
+            steps: a.1.steps_to(position) + b.1.steps_to(position),
+        }
+    }
+
+    /// Manhattan distance to the central port
+    pub fn distance(&self) -> i32 {
+        self.position.manhattan_length()
+    }
+}
+
+/// All crossings of each pair of wires except at the central port, ordered by distance. A point where wires meet more
+/// than once is a crossing for every pair of segments that meet there
+pub fn crossings(wires: &[Wire]) -> Vec<Crossing> {
+    const START: u8 = 0;
+    const VERTICAL: u8 = 1;
+    const END: u8 = 2;
+
+    let mut events = Vec::new();
+    let mut lines = HashMap::<(bool, i32), Vec<(usize, &Segment)>>::new();
+    for (w, wire) in wires.iter().enumerate() {
+        for segment in wire.segments.iter() {
+            if segment.is_horizontal() {
+                events.push((segment.min().x, START, w, segment));
+                events.push((segment.max().x, END, w, segment));
+                lines
+                    .entry((true, segment.start.y))
+                    .or_default()
+                    .push((w, segment));
+            } else {
+                events.push((segment.start.x, VERTICAL, w, segment));
+                lines
+                    .entry((false, segment.start.x))
+                    .or_default()
+                    .push((w, segment));
+            }
+        }
+    }
+    // Ends are inclusive, so at the same x horizontal segments start before and end after the vertical ones
+    events.sort_by_key(|&(x, kind, w, segment)| (x, kind, w, segment.steps));
+
+    let mut found = Vec::new();
+    let mut active = BTreeMap::<i32, Vec<(usize, &Segment)>>::new();
+    for (x, kind, w, segment) in events {
+        let y = segment.start.y;
+        match kind {
+            START => active.entry(y).or_default().push((w, segment)),
+            END => {
+                let horizontals = active.get_mut(&y).expect("Segment was started");
+                horizontals.retain(|&(hw, h)| hw != w || h != segment);
+                if horizontals.is_empty() {
+                    active.remove(&y);
+                }
+            }
+            _ => {
+                for (&y, horizontals) in active.range(segment.min().y..=segment.max().y) {
+                    for &(hw, h) in horizontals.iter().filter(|(hw, _)| *hw != w) {
+                        found.push(Crossing::new(Point::new(x, y), (w, segment), (hw, h)));
+                    }
+                }
+            }
+        }
+    }
+
+    // Overlapping segments on the same line
+    for ((horizontal, line), segments) in lines {
+        for (ix, &(aw, a)) in segments.iter().enumerate() {
+            for &(bw, b) in segments[ix + 1..].iter().filter(|(bw, _)| *bw != aw) {
+                let (from, to) = if horizontal {
+                    (a.min().x.max(b.min().x), a.max().x.min(b.max().x))
+                } else {
+                    (a.min().y.max(b.min().y), a.max().y.min(b.max().y))
+                };
+                for along in from..=to {
+                    let position = if horizontal {
+                        Point::new(along, line)
+                    } else {
+                        Point::new(line, along)
+                    };
+                    found.push(Crossing::new(position, (aw, a), (bw, b)));
+                }
+            }
+        }
+    }
+
+    found.retain(|c| c.position != Point::new(0, 0));
+    found.sort_by_key(|c| (c.distance(), *c));
+    found.dedup();
+    found
+}
+
+/// Draws the wires like the puzzle does, with up at the top: the central port is o, wires are - and | with a + where
+/// they turn and an X where different wires cross
+pub fn render(wires: &[Wire]) -> String {
+    // Rows are drawn from the lowest y, so flip the wires upside down
+    let flip = |p: Point| Point::new(p.x, -p.y);
+
+    let mut grid = Grid::new();
+    for wire in wires.iter() {
+        let last = wire.segments.len().saturating_sub(1);
+        for (ix, segment) in wire.segments.iter().enumerate() {
+            let offset = segment.end - segment.start;
+            let step = Point::new(offset.x.signum(), offset.y.signum());
+            let line = if segment.is_horizontal() { '-' } else { '|' };
+
+            let mut position = segment.start;
+            loop {
+                let turn =
+                    (position == segment.start && ix > 0) || (position == segment.end && ix < last);
+                grid.insert(flip(position), if turn { '+' } else { line });
+                if position == segment.end {
+                    break;
+                }
+                position += step;
+            }
+        }
+    }
+    for crossing in crossings(wires) {
+        grid.insert(flip(crossing.position), 'X');
+    }
+    grid.insert(Point::new(0, 0), 'o');
+
+    let (top_left, bottom_right) = grid.extends().expect("The central port is drawn");
+    grid.render_area(
+        &(top_left - Point::new(1, 1)),
+        &(bottom_right + Point::new(1, 1)),
+        |_, c| c.cloned().unwrap_or('.'),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    const EXAMPLES: [(&str, &str, i32); 3] = [
+        ("R8,U5,L5,D3", "U7,R6,D4,L4", 6),
+        (
+            "R75,D30,R83,U83,L12,D49,R71,U7,L72",
+            "U62,R66,U55,R34,D71,R55,D58,R83",
+            159,
+        ),
+        (
+            "R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51",
+            "U98,R91,D20,R16,D67,R40,U7,R15,U6,R7",
+            135,
+        ),
+    ];
+
+    fn wires(paths: &[&str]) -> Vec<Wire> {
+        paths.iter().map(|path| path.parse().unwrap()).collect()
+    }
+
+    /// Points that both wires visit, by walking every step
+    fn visited_by_both(a: &str, b: &str) -> HashSet<Point> {
+        let visited = |path: &str| -> HashSet<Point> {
+            path.parse::<Wire>()
+                .unwrap()
+                .first_visits()
+                .into_keys()
+                .collect()
+        };
+        visited(a).intersection(&visited(b)).cloned().collect()
+    }
+
+    #[test]
+    fn puzzle_examples() {
+        let found = crossings(&wires(&[EXAMPLES[0].0, EXAMPLES[0].1]));
+        assert_eq!(
+            found,
+            vec![
+                Crossing {
+                    position: Point::new(3, 3),
+                    wires: (0, 1),
+                    steps: 40
+                },
+                Crossing {
+                    position: Point::new(6, 5),
+                    wires: (0, 1),
+                    steps: 30
+                },
+            ]
+        );
+
+        for &(a, b, distance) in EXAMPLES.iter() {
+            let found = crossings(&wires(&[a, b]));
+            assert_eq!(found[0].distance(), distance);
+            assert_eq!(
+                found.iter().map(|c| c.position).collect::<HashSet<_>>(),
+                visited_by_both(a, b)
+            );
+        }
+    }
+
+    #[test]
+    fn steps_like_first_visits() {
+        // The first wire of the last pair passes (3,0) twice
+        let mut pairs = EXAMPLES.iter().map(|&(a, b, _)| (a, b)).collect::<Vec<_>>();
+        pairs.push(("R5,U2,L2,D4", "D1,R3,U1"));
+        for (a, b) in pairs {
+            let wires = wires(&[a, b]);
+            let (visits_a, visits_b) = (wires[0].first_visits(), wires[1].first_visits());
+            for crossing in crossings(&wires) {
+                let position = &crossing.position;
+                assert!(crossing.steps >= visits_a[position] + visits_b[position]);
+            }
+            assert_eq!(
+                crossings(&wires).iter().map(|c| c.steps).min(),
+                visits_a
+                    .iter()
+                    .filter_map(|(position, steps)| visits_b
+                        .get(position)
+                        .map(|other| steps + other))
+                    .min()
+            );
+        }
+    }
+
+    #[test]
+    fn draws_wires() {
+        assert_eq!(
+            render(&wires(&[EXAMPLES[0].0, EXAMPLES[0].1])),
+            "...........\n\
+             .+-----+...\n\
+             .|.....|...\n\
+             .|..+--X-+.\n\
+             .|..|..|.|.\n\
+             .|.-X--+.|.\n\
+             .|..|....|.\n\
+             .|.......|.\n\
+             .o-------+.\n\
+             ...........\n"
+        );
+    }
+
+    #[test]
+    fn overlaps_and_more_wires() {
+        // The first two wires share y = 2 from x = 3 to 5, the last two share y = 0 from the port to x = 3
+        let found = crossings(&wires(&["U2,R5", "R3,U2,R4", "R4,U4"]));
+        let pairs = |pair| {
+            found
+                .iter()
+                .filter(|c| c.wires == pair)
+                .map(|c| (c.position.x, c.position.y, c.steps))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(pairs((0, 1)), vec![(3, 2, 10), (4, 2, 12), (5, 2, 14)]);
+        assert_eq!(pairs((0, 2)), vec![(4, 2, 12)]);
+        assert_eq!(
+            pairs((1, 2)),
+            vec![(1, 0, 2), (2, 0, 4), (3, 0, 6), (4, 2, 12)]
+        );
+        assert_eq!(
+            found.iter().map(|c| c.position).collect::<HashSet<_>>(),
+            visited_by_both("U2,R5", "R3,U2,R4")
+                .union(&visited_by_both("R4,U4", "U2,R5"))
+                .chain(visited_by_both("R4,U4", "R3,U2,R4").iter())
+                .cloned()
+                .collect()
+        );
+    }
+}
@@ -0,0 +1,257 @@
+//! Counting passwords without trying every number
+//!
+//! The digits of a valid password never decrease, so there are few of them: a password of n digits is a choice of n
+//! digits from 1 to 9 with repetition. The counter walks the digits of the bounds of a range like a digit DP and
+//! remembers how many ways there are to finish a password from its last digit, the length of the group of equal
+//! digits it ends in and whether an earlier group already satisfied the repeat rule.
+
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Repeat {
+    /// A group of at least two adjacent equal digits
+    Pair,
+    /// A group of exactly two adjacent equal digits
+    ExactPair,
+}
+
+impl Repeat {
+    /// Whether a group of equal digits satisfies the rule, longer groups than three are counted as three
+    fn accepts(self, group: u32) -> bool {
+        match self {
+            Repeat::Pair => group >= 2,
+            Repeat::ExactPair => group == 2,
+        }
+    }
+
+    /// Whether any group of equal adjacent digits satisfies the rule
+    pub fn matches(self, digits: &[u8]) -> bool {
+        digits
+            .chunk_by(|a, b| a == b)
+            .any(|group| self.accepts(group.len().min(3) as u32))
+    }
+}
+
+/// Decimal digits of a number, the most significant first
+pub fn digits(n: u64) -> Vec<u8> {
+    n.to_string().bytes().map(|b| b - b'0').collect()
+}
+
+pub fn is_non_decreasing(digits: &[u8]) -> bool {
+    digits.windows(2).all(|pair| pair[0] <= pair[1])
+}
+
+pub fn is_valid(password: u64, repeat: Repeat) -> bool {
+    let digits = digits(password);
+    is_non_decreasing(&digits) && repeat.matches(&digits)
+}
+
+/// The last digit of a partial password, the length of its group and whether the repeat rule is met already
+type State = (u8, u32, bool);
+
+struct Counter {
+    repeat: Repeat,
+    memo: HashMap<(usize, State), u64>,
+}
+
+impl Counter {
+    fn new(repeat: Repeat) -> Counter {
+        Counter {
+            repeat,
+            memo: HashMap::new(),
+        }
+    }
+
+    fn push(&self, (last, group, found): State, digit: u8) -> State {
+        if digit == last {
+            (digit, (group + 1).min(3), found)
+        } else {
+            (digit, 1, found || self.repeat.accepts(group))
+        }
+    }
+
+    fn is_complete(&self, (_, group, found): State) -> bool {
+        found || self.repeat.accepts(group)
+    }
+
+    /// Ways to add the remaining digits to a partial password
+    fn finish(&mut self, remaining: usize, state: State) -> u64 {
+        if remaining == 0 {
+            return self.is_complete(state) as u64;
+        }
+        if let Some(&count) = self.memo.get(&(remaining, state)) {
+            return count;
+        }
+
+        let count = (state.0..=9)
+            .map(|digit| {
+                let next = self.push(state, digit);
+                self.finish(remaining - 1, next)
+            })
+            .sum();
+        self.memo.insert((remaining, state), count);
+        count
+    }
+
+    /// Valid passwords from 1 up to and including n
+    fn up_to(&mut self, n: u64) -> u64 {
+        let bound = digits(n);
+        // Before the first digit, which can not be 0
+        let start = (1, 0, false);
+
+        let mut count = 0;
+        for length in 1..bound.len() {
+            count += self.finish(length, start);
+        }
+
+        // Passwords as long as n, that have the digits of n up to some position and a lower digit there
+        let mut state = start;
+        for (ix, &digit) in bound.iter().enumerate() {
+            for lower in state.0..digit {
+                let next = self.push(state, lower);
+                count += self.finish(bound.len() - ix - 1, next);
+            }
+            if digit < state.0 {
+                return count;
+            }
+            state = self.push(state, digit);
+        }
+
+        count + self.is_complete(state) as u64
+    }
+}
+
+/// Number of valid passwords in the range, of any length
+pub fn count(range: RangeInclusive<u64>, repeat: Repeat) -> u64 {
+    let (start, end) = range.into_inner();
+    if start > end {
+        return 0;
+    }
+
+    let mut counter = Counter::new(repeat);
+    let below = if start == 0 {
+        0
+    } else {
+        counter.up_to(start - 1)
+    };
+    counter.up_to(end) - below
+}
+
+/// Smallest number not below n with digits that never decrease, None when it does not fit
+fn non_decreasing_from(n: u64) -> Option<u64> {
+    let mut digits = digits(n);
+    if let Some(ix) = (1..digits.len()).find(|&ix| digits[ix] < digits[ix - 1]) {
+        let fill = digits[ix - 1];
+        digits[ix..].iter_mut().for_each(|digit| *digit = fill);
+    }
+
+    digits.iter().try_fold(0u64, |n, &digit| {
+        n.checked_mul(10)?.checked_add(digit as u64)
+    })
+}
+
+/// Numbers in a range with digits that never decrease, in increasing order
+pub struct NonDecreasing {
+    next: Option<u64>,
+    end: u64,
+}
+
+pub fn non_decreasing(range: RangeInclusive<u64>) -> NonDecreasing {
+    let (start, end) = range.into_inner();
+    NonDecreasing {
+        next: Some(start),
+        end,
+    }
+}
+
+impl Iterator for NonDecreasing {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        match self.next.and_then(non_decreasing_from) {
+            Some(n) if n <= self.end => {
+                self.next = n.checked_add(1);
+                Some(n)
+            }
+            _ => {
+                self.next = None;
+                None
+            }
+        }
+    }
+}
+
+/// Valid passwords in a range in increasing order, skipping over the numbers with decreasing digits
+pub fn candidates(range: RangeInclusive<u64>, repeat: Repeat) -> impl Iterator<Item = u64> {
+    non_decreasing(range).filter(move |&n| repeat.matches(&digits(n)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brute_force(range: RangeInclusive<u64>, repeat: Repeat) -> Vec<u64> {
+        range.filter(|&n| is_valid(n, repeat)).collect()
+    }
+
+    #[test]
+    fn puzzle_examples() {
+        assert!(is_valid(111111, Repeat::Pair));
+        assert!(!is_valid(223450, Repeat::Pair));
+        assert!(!is_valid(123789, Repeat::Pair));
+
+        assert!(is_valid(112233, Repeat::ExactPair));
+        assert!(!is_valid(123444, Repeat::ExactPair));
+        assert!(is_valid(111122, Repeat::ExactPair));
+    }
+
+    #[test]
+    fn counts_like_brute_force() {
+        for &repeat in [Repeat::Pair, Repeat::ExactPair].iter() {
+            for range in [
+                0..=0,
+                0..=99,
+                5..=11,
+                123..=98765,
+                111111..=111111,
+                100000..=234567,
+            ]
+            .iter()
+            {
+                let valid = brute_force(range.clone(), repeat);
+                assert_eq!(
+                    count(range.clone(), repeat),
+                    valid.len() as u64,
+                    "{:?}",
+                    range
+                );
+                assert_eq!(
+                    candidates(range.clone(), repeat).collect::<Vec<u64>>(),
+                    valid
+                );
+            }
+        }
+
+        #[allow(clippy::reversed_empty_ranges)]
+        let empty = 10..=1;
+        assert_eq!(count(empty, Repeat::Pair), 0);
+    }
+
+    #[test]
+    fn long_passwords() {
+        // There are 27 choose 8 non-decreasing sequences of 19 digits from 1 to 9, all of them repeat a digit
+        assert_eq!(
+            count(10u64.pow(18)..=10u64.pow(19) - 1, Repeat::Pair),
+            2_220_075
+        );
+        assert_eq!(
+            candidates(u64::MAX - 1000..=u64::MAX, Repeat::Pair).next(),
+            None
+        );
+        assert_eq!(
+            candidates(11_111_111_111_111_111_100..=u64::MAX, Repeat::ExactPair).next(),
+            Some(11_111_111_111_111_111_122)
+        );
+    }
+}
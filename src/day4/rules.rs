@@ -0,0 +1,315 @@
+//! Password rules that can be combined
+//!
+//! A password is valid when it passes every rule of a rule set. Rules are written as on the command line:
+//!     non-decreasing  digits never decrease from left to right
+//!     group>=k        some group of adjacent equal digits is at least k long
+//!     group=k         some group of adjacent equal digits is exactly k long
+//!     length=n        the password has n digits
+//!     range=a-b       the password is between a and b, both included
+//!     forbid=digits   none of the digits appear, e.g. forbid=09
+//! Part 1 of the puzzle is `length=6 range=a-b non-decreasing group>=2`, part 2 has `group=2` instead.
+
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use super::counter::{count, digits, is_non_decreasing, non_decreasing, Repeat};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Rule {
+    NonDecreasing,
+    GroupAtLeast(usize),
+    GroupExactly(usize),
+    Length(usize),
+    Range(RangeInclusive<u64>),
+    Forbidden(Vec<u8>),
+}
+
+impl Rule {
+    /// Whether the password, of which digits are the decimal digits, passes the rule
+    pub fn check(&self, password: u64, digits: &[u8]) -> bool {
+        let mut groups = digits.chunk_by(|a, b| a == b).map(|group| group.len());
+        match self {
+            Rule::NonDecreasing => is_non_decreasing(digits),
+            Rule::GroupAtLeast(k) => groups.any(|length| length >= *k),
+            Rule::GroupExactly(k) => groups.any(|length| length == *k),
+            Rule::Length(n) => digits.len() == *n,
+            Rule::Range(range) => range.contains(&password),
+            Rule::Forbidden(forbidden) => !digits.iter().any(|d| forbidden.contains(d)),
+        }
+    }
+
+    /// The numbers that can pass the rule at all
+    fn bounds(&self) -> RangeInclusive<u64> {
+        match self {
+            Rule::Range(range) => range.clone(),
+            Rule::Length(n) => {
+                // No number has 0 digits
+                let lowest = match n {
+                    0 => None,
+                    1 => Some(0),
+                    _ => 10u64.checked_pow(*n as u32 - 1),
+                };
+                let highest = 10u64.checked_pow(*n as u32).map_or(u64::MAX, |p| p - 1);
+                match lowest {
+                    Some(lowest) => lowest..=highest,
+                    #[allow(clippy::reversed_empty_ranges)]
+                    None => 1..=0,
+                }
+            }
+            _ => 0..=u64::MAX,
+        }
+    }
+}
+
+impl From<Repeat> for Rule {
+    fn from(repeat: Repeat) -> Rule {
+        match repeat {
+            Repeat::Pair => Rule::GroupAtLeast(2),
+            Repeat::ExactPair => Rule::GroupExactly(2),
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rule::NonDecreasing => write!(f, "non-decreasing"),
+            Rule::GroupAtLeast(k) => write!(f, "group>={}", k),
+            Rule::GroupExactly(k) => write!(f, "group={}", k),
+            Rule::Length(n) => write!(f, "length={}", n),
+            Rule::Range(range) => write!(f, "range={}-{}", range.start(), range.end()),
+            Rule::Forbidden(digits) => {
+                write!(f, "forbid=")?;
+                digits.iter().try_for_each(|d| write!(f, "{}", d))
+            }
+        }
+    }
+}
+
+/// Range written as a-b
+pub fn parse_range(s: &str) -> Result<RangeInclusive<u64>, String> {
+    let mut bounds = s.splitn(2, '-').map(|b| b.trim().parse::<u64>());
+    match (bounds.next(), bounds.next()) {
+        (Some(Ok(start)), Some(Ok(end))) => Ok(start..=end),
+        _ => Err(format!("Invalid range {:?}, expected a-b", s)),
+    }
+}
+
+impl FromStr for Rule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Rule, String> {
+        let positive = |value: &str| match value.parse::<usize>() {
+            Ok(n) if n > 0 => Ok(n),
+            _ => Err(format!(
+                "Invalid rule {:?}, {:?} is not a positive number",
+                s, value
+            )),
+        };
+
+        if s == "non-decreasing" {
+            Ok(Rule::NonDecreasing)
+        } else if let Some(k) = s.strip_prefix("group>=") {
+            positive(k).map(Rule::GroupAtLeast)
+        } else if let Some(k) = s.strip_prefix("group=") {
+            positive(k).map(Rule::GroupExactly)
+        } else if let Some(n) = s.strip_prefix("length=") {
+            positive(n).map(Rule::Length)
+        } else if let Some(range) = s.strip_prefix("range=") {
+            parse_range(range).map(Rule::Range)
+        } else if let Some(digits) = s.strip_prefix("forbid=") {
+            digits
+                .chars()
+                .map(|c| c.to_digit(10).map(|d| d as u8))
+                .collect::<Option<Vec<u8>>>()
+                .filter(|digits| !digits.is_empty())
+                .map(Rule::Forbidden)
+                .ok_or_else(|| format!("Invalid rule {:?}, expected digits to forbid", s))
+        } else {
+            Err(format!(
+                "Unknown rule {:?}, expected non-decreasing, group>=k, group=k, length=n, range=a-b or forbid=digits",
+                s
+            ))
+        }
+    }
+}
+
+/// Rules that a password must all pass
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RuleSet {
+    pub rules: Vec<Rule>,
+}
+
+impl RuleSet {
+    pub fn new() -> RuleSet {
+        RuleSet::default()
+    }
+
+    /// The rules of the puzzle for passwords in the range
+    pub fn puzzle(range: RangeInclusive<u64>, repeat: Repeat) -> RuleSet {
+        RuleSet::new()
+            .with(Rule::Length(6))
+            .with(Rule::Range(range))
+            .with(Rule::NonDecreasing)
+            .with(repeat.into())
+    }
+
+    pub fn with(mut self, rule: Rule) -> RuleSet {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Rules of both sets
+    pub fn and(mut self, other: RuleSet) -> RuleSet {
+        self.rules.extend(other.rules);
+        self
+    }
+
+    pub fn is_valid(&self, password: u64) -> bool {
+        let digits = digits(password);
+        self.rules.iter().all(|rule| rule.check(password, &digits))
+    }
+
+    /// The numbers allowed by all range and length rules
+    pub fn bounds(&self) -> RangeInclusive<u64> {
+        self.rules
+            .iter()
+            .map(Rule::bounds)
+            .fold(0..=u64::MAX, |a, b| {
+                *a.start().max(b.start())..=*a.end().min(b.end())
+            })
+    }
+
+    /// Valid passwords in increasing order. With non-decreasing digits only those numbers are tried, otherwise every
+    /// number within the bounds, so such a set should have a range or length
+    pub fn candidates(&self) -> Box<dyn Iterator<Item = u64> + '_> {
+        let bounds = self.bounds();
+        if self.rules.contains(&Rule::NonDecreasing) {
+            Box::new(non_decreasing(bounds).filter(move |&n| self.is_valid(n)))
+        } else {
+            Box::new(bounds.filter(move |&n| self.is_valid(n)))
+        }
+    }
+
+    /// The repeat rule when the other rules only bound the passwords and make their digits non-decreasing, as in the
+    /// puzzle, so that they can be counted without trying them
+    fn repeat(&self) -> Option<Repeat> {
+        let mut repeat = None;
+        let mut non_decreasing = false;
+        for rule in self.rules.iter() {
+            match rule {
+                Rule::Range(_) | Rule::Length(_) => {}
+                Rule::NonDecreasing => non_decreasing = true,
+                Rule::GroupAtLeast(2) if repeat.is_none() => repeat = Some(Repeat::Pair),
+                Rule::GroupExactly(2) if repeat.is_none() => repeat = Some(Repeat::ExactPair),
+                _ => return None,
+            }
+        }
+        repeat.filter(|_| non_decreasing)
+    }
+
+    /// Number of valid passwords, the puzzle rules are counted by the digits of the bounds and others by trying the
+    /// candidates
+    pub fn count(&self) -> u64 {
+        match self.repeat() {
+            Some(repeat) => count(self.bounds(), repeat),
+            None => self.candidates().count() as u64,
+        }
+    }
+}
+
+impl fmt::Display for RuleSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rules = self
+            .rules
+            .iter()
+            .map(|rule| rule.to_string())
+            .collect::<Vec<String>>();
+        write!(f, "{}", rules.join(" "))
+    }
+}
+
+impl FromStr for RuleSet {
+    type Err = String;
+
+    /// Rules separated by whitespace
+    fn from_str(s: &str) -> Result<RuleSet, String> {
+        s.split_whitespace()
+            .map(str::parse)
+            .collect::<Result<Vec<Rule>, String>>()
+            .map(|rules| RuleSet { rules })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_rules() {
+        let rules = "length=6 range=100000-234567 non-decreasing group>=2 group=3 forbid=09"
+            .parse::<RuleSet>()
+            .unwrap();
+        assert_eq!(
+            rules.rules,
+            vec![
+                Rule::Length(6),
+                Rule::Range(100000..=234567),
+                Rule::NonDecreasing,
+                Rule::GroupAtLeast(2),
+                Rule::GroupExactly(3),
+                Rule::Forbidden(vec![0, 9]),
+            ]
+        );
+        assert_eq!(rules.to_string().parse(), Ok(rules));
+
+        for invalid in [
+            "group>=0",
+            "group=x",
+            "range=5",
+            "forbid=",
+            "forbid=1a",
+            "increasing",
+        ]
+        .iter()
+        {
+            assert!(invalid.parse::<Rule>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn composes_rules() {
+        for &repeat in [Repeat::Pair, Repeat::ExactPair].iter() {
+            let rules = RuleSet::puzzle(123456..=234567, repeat);
+            assert_eq!(rules.count(), count(123456..=234567, repeat));
+            assert_eq!(rules.count(), rules.candidates().count() as u64);
+        }
+
+        // Bounds of 19 digits are counted without trying any password
+        let rules = "length=19 non-decreasing group>=2"
+            .parse::<RuleSet>()
+            .unwrap();
+        assert_eq!(rules.count(), 2_220_075);
+
+        let rules = RuleSet::new()
+            .with(Rule::Length(4))
+            .with(Rule::NonDecreasing)
+            .and("group=3 forbid=12".parse().unwrap());
+        assert_eq!(rules.bounds(), 1000..=9999);
+        assert_eq!(
+            rules.candidates().take(3).collect::<Vec<u64>>(),
+            vec![3334, 3335, 3336]
+        );
+        assert!(rules.candidates().all(|n| !n.to_string().contains('1')));
+
+        // Without non-decreasing digits every number in the bounds is tried
+        let rules = "range=0-200 group>=3".parse::<RuleSet>().unwrap();
+        assert_eq!(rules.candidates().collect::<Vec<u64>>(), vec![111]);
+
+        assert_eq!(Rule::Length(1).bounds(), 0..=9);
+        assert_eq!(Rule::Length(20).bounds(), 10u64.pow(19)..=u64::MAX);
+        assert!(Rule::Length(21).bounds().is_empty());
+        assert!(Rule::Length(0).bounds().is_empty());
+    }
+}
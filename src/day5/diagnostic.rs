@@ -0,0 +1,141 @@
+//! Thermal Environment Supervision Terminal diagnostics
+//!
+//! The diagnostic program reads the ID of the system to test and runs a test for each of its parts, outputting 0 when
+//! the test passed and how far off the result was otherwise. The last output is the diagnostic code of the system.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::cpu::*;
+
+/// System ID of the ship's air conditioner unit
+pub const AIR_CONDITIONER: CpuWidth = 1;
+/// System ID of the ship's thermal radiator controller
+pub const THERMAL_RADIATOR: CpuWidth = 5;
+
+/// Value written by the program, with the address of the instruction that wrote it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Output {
+    pub value: CpuWidth,
+    pub pc: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DiagnosticError {
+    /// A test before the diagnostic code did not output 0, outputs are counted from 0
+    TestFailed {
+        index: usize,
+        pc: usize,
+        value: CpuWidth,
+    },
+    /// The program finished without any output
+    NoOutput,
+    /// The program asked for more input than the system ID
+    WaitingForInput { pc: usize },
+}
+
+impl fmt::Display for DiagnosticError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiagnosticError::TestFailed { index, pc, value } => {
+                write!(f, "Test {} failed with {} at address {}", index, value, pc)
+            }
+            DiagnosticError::NoOutput => write!(f, "The diagnostic program has no output"),
+            DiagnosticError::WaitingForInput { pc } => {
+                write!(
+                    f,
+                    "The diagnostic program waits for more input at address {}",
+                    pc
+                )
+            }
+        }
+    }
+}
+
+impl Error for DiagnosticError {}
+
+/// All outputs of a diagnostic run
+#[derive(Clone, Debug, PartialEq)]
+pub struct Report {
+    pub system: CpuWidth,
+    pub outputs: Vec<Output>,
+}
+
+impl Report {
+    /// The diagnostic code when all tests before it passed, otherwise the first test that failed
+    pub fn check(&self) -> Result<CpuWidth, DiagnosticError> {
+        let (code, tests) = self.outputs.split_last().ok_or(DiagnosticError::NoOutput)?;
+
+        match tests.iter().position(|test| test.value != 0) {
+            Some(index) => Err(DiagnosticError::TestFailed {
+                index,
+                pc: tests[index].pc,
+                value: tests[index].value,
+            }),
+            None => Ok(code.value),
+        }
+    }
+}
+
+/// Runs the diagnostic program for a system until it finishes, one instruction at a time to know where every output
+/// comes from
+pub fn run(program: &mut Program, system: CpuWidth) -> Result<Report, DiagnosticError> {
+    program.add_input(system);
+
+    let mut outputs = Vec::new();
+    while !program.is_finished() {
+        let (pc, steps, written) = (program.pc(), program.steps(), program.output.len());
+        execute_until(program, steps + 1);
+        if !program.is_finished() && program.steps() == steps {
+            return Err(DiagnosticError::WaitingForInput { pc });
+        }
+
+        outputs.extend(
+            program.output[written..]
+                .iter()
+                .map(|&value| Output { value, pc }),
+        );
+    }
+
+    Ok(Report { system, outputs })
+}
+
+/// Diagnostic code of a system, after checking that all its tests passed
+pub fn diagnose(input: &str, system: CpuWidth) -> Result<CpuWidth, DiagnosticError> {
+    run(&mut read(input), system)?.check()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_tests() {
+        assert_eq!(diagnose("104,0,104,0,104,42,99", 1), Ok(42));
+
+        // Outputs the input in the second test
+        let program = "3,0,104,0,4,0,104,42,99";
+        assert_eq!(
+            run(&mut read(program), 0).unwrap().outputs,
+            vec![
+                Output { value: 0, pc: 2 },
+                Output { value: 0, pc: 4 },
+                Output { value: 42, pc: 6 },
+            ]
+        );
+        assert_eq!(
+            diagnose(program, 7),
+            Err(DiagnosticError::TestFailed {
+                index: 1,
+                pc: 4,
+                value: 7
+            })
+        );
+
+        assert_eq!(diagnose("3,0,99", 1), Err(DiagnosticError::NoOutput));
+        assert_eq!(
+            diagnose("3,0,104,0,3,0,99", 1),
+            Err(DiagnosticError::WaitingForInput { pc: 4 })
+        );
+    }
+}
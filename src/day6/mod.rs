@@ -1,11 +1,11 @@
-use crate::common::import_lines;
+use crate::common::puzzle_input;
 
 struct Orbit {
     center: String,
     sattelite: String,
 }
 
-struct Body {
+pub struct Body {
     name: String,
     orbiting: Option<usize>,
     sattelites: Vec<usize>,
@@ -28,7 +28,7 @@ fn count_orbits_recursive(body: &Body, bodies: &Vec<Body>) -> i32 {
     orbits + 1
 }
 
-fn build_body_tree(input: &str) -> Vec<Body> {
+pub fn build_body_tree(input: &str) -> Vec<Body> {
     // Parse the input into an Orbit collection
     let orbits = input
         .lines()
@@ -77,20 +77,24 @@ pub fn test() {
     println!("Day6 test has {} total orbits", total_orbits)
 }
 
-pub fn part1() {
-    let bodies = build_body_tree(&import_lines("src/day6/input.txt"));
+pub fn solve1(input: &str) -> i32 {
+    let bodies = build_body_tree(input);
 
     let mut total_orbits = 0;
     for body in &bodies {
         total_orbits += count_orbits_recursive(body, &bodies);
     }
-    println!("Day6 part 1 has {} total orbits", total_orbits)
+    total_orbits
 }
 
-pub fn part2() {
+pub fn part1() {
+    println!("Day6 part 1 has {} total orbits", solve1(&puzzle_input(6)))
+}
+
+pub fn solve2(input: &str) -> Option<usize> {
     // let source = "COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L\nK)YOU\nI)SAN";
     // let bodies = build_bodies(source);
-    let bodies = build_body_tree(&import_lines("src/day6/input.txt"));
+    let bodies = build_body_tree(input);
 
     let san_index = bodies
         .iter()
@@ -128,11 +132,15 @@ pub fn part2() {
         if let Some(you_index) = you_transfers.iter().position(|l| l == san_location) {
             // As this is the first common position for SAN and YOU, both movements combined brings YOU to SAN.
             // Were are interested in the number of moves so need to add 1 for each of san_index and you_index
-            println!(
-                "Day6 part 2 has {} transfers from YOU to SAN",
-                san_index + 1 + you_index + 1
-            );
-            break;
+            return Some(san_index + 1 + you_index + 1);
         }
     }
+    None
+}
+
+pub fn part2() {
+    match solve2(&puzzle_input(6)) {
+        Some(transfers) => println!("Day6 part 2 has {} transfers from YOU to SAN", transfers),
+        None => println!("Day6 part 2 has no route from YOU to SAN"),
+    }
 }
@@ -1,49 +1,122 @@
 use permute;
 
+use crate::common::puzzle_input;
 use crate::cpu::*;
 
-fn input() -> &'static str {
-    "3,8,1001,8,10,8,105,1,0,0,21,42,67,88,105,114,195,276,357,438,99999,3,9,101,4,9,9,102,3,9,9,1001,9,2,9,102,4,9,9,4,9,99,3,9,1001,9,4,9,102,4,9,9,101,2,9,9,1002,9,5,9,1001,9,2,9,4,9,99,3,9,1001,9,4,9,1002,9,4,9,101,2,9,9,1002,9,2,9,4,9,99,3,9,101,4,9,9,102,3,9,9,1001,9,5,9,4,9,99,3,9,102,5,9,9,4,9,99,3,9,102,2,9,9,4,9,3,9,101,1,9,9,4,9,3,9,101,2,9,9,4,9,3,9,1001,9,2,9,4,9,3,9,102,2,9,9,4,9,3,9,1002,9,2,9,4,9,3,9,1001,9,1,9,4,9,3,9,102,2,9,9,4,9,3,9,102,2,9,9,4,9,3,9,101,2,9,9,4,9,99,3,9,1002,9,2,9,4,9,3,9,1001,9,2,9,4,9,3,9,102,2,9,9,4,9,3,9,102,2,9,9,4,9,3,9,102,2,9,9,4,9,3,9,102,2,9,9,4,9,3,9,1001,9,1,9,4,9,3,9,101,1,9,9,4,9,3,9,101,1,9,9,4,9,3,9,1002,9,2,9,4,9,99,3,9,102,2,9,9,4,9,3,9,101,1,9,9,4,9,3,9,1002,9,2,9,4,9,3,9,1002,9,2,9,4,9,3,9,1002,9,2,9,4,9,3,9,102,2,9,9,4,9,3,9,101,2,9,9,4,9,3,9,102,2,9,9,4,9,3,9,1001,9,2,9,4,9,3,9,1001,9,1,9,4,9,99,3,9,1002,9,2,9,4,9,3,9,1001,9,1,9,4,9,3,9,101,2,9,9,4,9,3,9,1001,9,1,9,4,9,3,9,101,2,9,9,4,9,3,9,1001,9,1,9,4,9,3,9,102,2,9,9,4,9,3,9,1002,9,2,9,4,9,3,9,1002,9,2,9,4,9,3,9,1001,9,2,9,4,9,99,3,9,1002,9,2,9,4,9,3,9,101,1,9,9,4,9,3,9,1002,9,2,9,4,9,3,9,102,2,9,9,4,9,3,9,1001,9,1,9,4,9,3,9,1002,9,2,9,4,9,3,9,1001,9,1,9,4,9,3,9,101,2,9,9,4,9,3,9,1001,9,1,9,4,9,3,9,1002,9,2,9,4,9,99"
+fn input() -> String {
+    puzzle_input(7)
 }
 
-fn find_max_output(input: &str, phase_settings: Vec<i64>) -> i64 {
-    // Clean program state
-    let program = read(input);
+#[derive(Clone, Copy, PartialEq)]
+pub enum Mode {
+    /// Each amplifier runs once, the output of the last amplifier is the result
+    Linear,
+    /// The output of the last amplifier is fed back into the first until all amplifiers are finished
+    Feedback,
+}
 
-    let mut max_output = std::i64::MIN;
+/// Signals produced by a run of an amplifier chain
+pub struct Signals {
+    /// All signals produced by each amplifier, in order
+    pub amplifiers: Vec<Vec<CpuWidth>>,
+}
 
-    // Phase input permutations for phase settings
-    let phase_inputs = permute::permute(phase_settings);
+impl Signals {
+    /// The last signal produced by the last amplifier
+    pub fn output(&self) -> Option<CpuWidth> {
+        self.amplifiers.last().and_then(|s| s.last().cloned())
+    }
+}
 
-    for phase_input in phase_inputs {
-        // Initialize and execute first phase, set input signal to 0.
-        let mut phase = program.clone();
-        phase.interactive = false;
+pub struct AmplifierChain {
+    program: Program,
+    mode: Mode,
+}
 
-        phase.add_input(phase_input[0]);
-        phase.add_input(0);
-        execute(&mut phase);
+impl AmplifierChain {
+    pub fn new(program: Program, mode: Mode) -> AmplifierChain {
+        AmplifierChain { program, mode }
+    }
 
-        // Execute the rest of the phases, set input signal to output of previous phase.
-        for ix in 1..phase_input.len() {
-            let mut next_phase = program.clone();
-            next_phase.interactive = false;
+    /// Runs a chain of amplifiers, one for each phase setting. The first amplifier receives signal 0
+    pub fn run(&self, phase_settings: &[CpuWidth]) -> Signals {
+        // Create and prepare a program for each amplifier with its phase setting
+        let mut amplifiers = phase_settings
+            .iter()
+            .map(|phase| {
+                let mut amplifier = self.program.clone();
+                amplifier.interactive = false;
+                amplifier.add_input(*phase);
+                amplifier
+            })
+            .collect::<Vec<Program>>();
+
+        let mut signals = Signals {
+            amplifiers: vec![vec![]; amplifiers.len()],
+        };
+
+        let mut pending = vec![0];
+        loop {
+            for (ix, amplifier) in amplifiers.iter_mut().enumerate() {
+                for signal in pending.drain(..) {
+                    amplifier.add_input(signal);
+                }
+                execute(amplifier);
 
-            next_phase.add_input(phase_input[ix]);
-            next_phase.add_input(phase.get_output(0).expect("Error, No output for phase"));
-            execute(&mut next_phase);
+                // Everything this amplifier produced is input for the next one. The program is either finished or
+                // waiting for input at this point, so flushing only drops input that was already consumed
+                pending = amplifier.output.clone();
+                signals.amplifiers[ix].extend(&pending);
+                amplifier.flush();
+            }
 
-            // Remember for next phase or final output
-            phase = next_phase;
+            // Stop when finished, or when the amplifiers are starved of input in feedback mode
+            if self.mode == Mode::Linear
+                || amplifiers.iter().all(|p| p.is_finished())
+                || pending.is_empty()
+            {
+                break;
+            }
         }
 
-        max_output = std::cmp::max(
-            max_output,
-            phase.get_output(0).expect("Error, no program output"),
-        );
+        signals
     }
 
-    max_output
+    /// Tries all permutations of the phase settings, spread over all available cores.
+    /// Returns the phase settings producing the highest output and that output
+    pub fn find_max_output(&self, phase_settings: Vec<CpuWidth>) -> (Vec<CpuWidth>, CpuWidth) {
+        let permutations = permute::permute(phase_settings);
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let chunk = permutations.len().div_ceil(threads);
+
+        std::thread::scope(|scope| {
+            let workers = permutations
+                .chunks(std::cmp::max(chunk, 1))
+                .map(|permutations| {
+                    scope.spawn(move || {
+                        permutations
+                            .iter()
+                            .filter_map(|phases| {
+                                self.run(phases).output().map(|o| (phases.clone(), o))
+                            })
+                            .max_by_key(|(_, output)| *output)
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            workers
+                .into_iter()
+                .filter_map(|worker| worker.join().expect("Amplifier worker panicked"))
+                .max_by_key(|(_, output)| *output)
+                .expect("Error, no program output")
+        })
+    }
+}
+
+fn find_max_output(input: &str, phase_settings: Vec<i64>) -> i64 {
+    AmplifierChain::new(read(input), Mode::Linear)
+        .find_max_output(phase_settings)
+        .1
 }
 
 pub fn test() {
@@ -56,66 +129,66 @@ pub fn test() {
     );
 }
 
-pub fn part1() {
-    println!(
-        "Maximum output : {}",
-        find_max_output(input(), vec![0, 1, 2, 3, 4])
-    );
+pub fn solve1(input: &str) -> CpuWidth {
+    find_max_output(input, vec![0, 1, 2, 3, 4])
 }
 
-pub fn part2() {
+pub fn solve2(input: &str) -> CpuWidth {
     // let input =
     // 	"3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5";
 
-    let program = read(input());
-    let phase_inputs = permute::permute(vec![9, 8, 7, 6, 5]);
-
-    let mut max_output = std::i64::MIN;
-
-    // Try all permutations of the phase settings
-    for phase_input in phase_inputs {
-        // Create programs for each amplifier
-        let mut amplifiers = [
-            program.clone(),
-            program.clone(),
-            program.clone(),
-            program.clone(),
-            program.clone(),
-        ];
-
-        // Prepare all amplifier programs with 1 input signal, the phase input setting
-        for i in 0..amplifiers.len() {
-            let amplifier = &mut amplifiers[i];
-            amplifier.interactive = false;
-            amplifier.add_input(phase_input[i]);
-        }
+    let (_, max_output) =
+        AmplifierChain::new(read(input), Mode::Feedback).find_max_output(vec![9, 8, 7, 6, 5]);
 
-        // Execute the amplifier programs until they are all finished
-        let mut last_output = 0;
-        loop {
-            for i in 0..amplifiers.len() {
-                let amplifier = &mut amplifiers[i];
+    max_output
+}
 
-                amplifier.add_input(last_output); // Add the output from the previous amplifier as input signal
-                execute(amplifier);
+pub fn part1() {
+    println!("Maximum output : {}", solve1(&input()));
+}
 
-                // Store the output so we can use it as input for the next program
-                last_output = amplifier.get_output(0).expect("Error, no output produced");
+pub fn part2() {
+    println!(
+        "Maximum output produced by amplifier E: {}",
+        solve2(&input())
+    );
+}
 
-                // At this point the program is either finished or paused, waiting for input
-                // We now reset the input queue, any input needed on subsequent runs is added from the output
-                // of the previous amplifier program
-                amplifier.flush();
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            if amplifiers.iter().all(|p| p.is_finished()) {
-                break;
-            }
-        }
+    #[test]
+    fn linear_chain() {
+        let chain = AmplifierChain::new(
+            read("3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0"),
+            Mode::Linear,
+        );
 
-        // We can use last_output here because that will always be the output of amplifier E at this point
-        max_output = std::cmp::max(max_output, last_output);
+        let signals = chain.run(&[4, 3, 2, 1, 0]);
+        assert_eq!(
+            signals.amplifiers,
+            vec![vec![4], vec![43], vec![432], vec![4321], vec![43210]]
+        );
+        assert_eq!(
+            chain.find_max_output(vec![0, 1, 2, 3, 4]),
+            (vec![4, 3, 2, 1, 0], 43210)
+        );
     }
 
-    println!("Maximum output produced by amplifier E: {}", max_output);
+    #[test]
+    fn feedback_chain() {
+        let chain = AmplifierChain::new(
+            read("3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5"),
+            Mode::Feedback,
+        );
+
+        let signals = chain.run(&[9, 8, 7, 6, 5]);
+        assert_eq!(signals.amplifiers[4].len(), 5);
+        assert_eq!(signals.output(), Some(139629729));
+        assert_eq!(
+            chain.find_max_output(vec![5, 6, 7, 8, 9]),
+            (vec![9, 8, 7, 6, 5], 139629729)
+        );
+    }
 }
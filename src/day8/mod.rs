@@ -1,8 +1,10 @@
-fn input() -> &'static str {
-    "222200222222120222222222222222220222202020022122222222222220222221222222220122022222222222222222210222220222121222222022101222222222222222222221222220222012222222222122222222222222220222202022122222222222222221222220221222220122222222222222222222201222220222222222222022111222222222222222222222222221222100222222022022222222222222221222212020222122222222222222222221221222220222122222222222222222220222221222222222222222121222222222222222222221222221222220222222120122222222222222222222202120022022222222222222222220221222222222022222222222222222212222222222022222222122112222222222222222222221222222222110222222111022222222222222220222212220022222222222222221221221222222222222122222222222222222210222222222121222222222020222222222222222222220222222222100222222112022222222222222222222212120122022222222222222220222221222221122222222222222222222201222221222220222222022121222222222202222222222212221222012222222111122222222222222220222212221022222222222222220221222221222221022222222222222222222222222222222021222222122020222222222222222222220212222222002222222012222222222222222220222212220022122222222222220221221221222210122222222222222222222202222221222220222222022112222222222212222222221202021222222222222200022222222222222222222202021022022222222222222222221220222202122222222222222222222210222220222121222222222221222222222202222222220222221222001222222210122222222222222202222212021222022222222222221221221220222201022022222222222222222201222221222221222222122121222222222202222222221202120222011222222020122222222222222222222212020122022222222222220220220220222221122222222222222222222200222220222022222222222110222222222222222222222222021222001222222200222222222222222222222212021222222222222222222220221222222221222102222222222222222210222222222022222222022211222220222212222222221212022222211222222120222222222222222220222211222022222222222222221220222222222220022112222222222222222221222220222121222222122211222220222212222222221202020222002222222211022222222222222210222211222222022222222222220221222221222201022122222222222222222202222220202221222222022201222220222202222222221202122222222222222202022222222222212222222211120222222222222222222222220222222220222022222222222222222220222222222020222220022211222222222212222222222202122222221222222112122222222222222221222211122222122222222222221221221221222222022222222222222222222222222220222122222222022222222220222202222222221202121222201222222110222221222222212221222211221122022222222222222221220221222220222022222222222222222201222220202221222220022221222222222202222222220202120222102222222020222221222222222201222222120122222222222222220221220220222220022012222222222222222200222222212122222222122001222222222212222222222212020222210222222120022220222222202220222211020122022222222222221220221222222210222202222222222222222210222221212221222220022222222221222202222222221212120222221222222110222221222222222220222202022222222222222222222222221220222220122012222222222222222201222221212121222221222221022222222222222222220222121222201222222221122220222222002210222222221122122222222222221222222221222201122122222222222222222211222020212222222222022121222220222202222222220202121222222222222100022221222222002222222200020022222222222222221221220220222222022202222222222222222222222022212222222221022210022222222222222222221212122222120222222202022221222222212210222202022122022222022222021221220222222212122012222222222220222201222121222121222222122210022220222222221222222202121222112222222210022222222222012211222220221222222222122222120220220222222222122222222022222221222200222220212022222221022000022221222222211222222202122222102222222100222222222222112211222221120122122222222222022222222221222200122222222222222221222200222222212102222221222220122221222202211222222222220222120222222211122221222222022201222212021022222222222222221222221221222200222122221222222221222200222220222010222221022120022221222202211222221202222222222222222121222221222222002220222201120022222222122222122221220221222222022012220122222222222211222221222102222221122100122222222202221222220222221222102222222101122220222222002212222200122022022222222222220221222221222022122202220022222222222201222022212201222222122210222221222222210222220212020222020222222211222220222222012221222211220122122222122222122222220222222000022122222022222222222221222022202011222220022021122222222202222222222212021222010222222202222221222222122210222200020122022222122222121221221222222001222122222122222220222222222221222201222220022111122222222222201222220202022222121222222120222222222220212211222202121022022222122222222221221222222122122202222122222222222220222120212222222220222110220220222212210222221212222222000222222112122220222220002212222212122222022222222222222220220220222101122112222222222222222212222220212111222221022110121222222222222222220222022222112222222011222221222221012200222020020122222222122022121221220221222102022002221022222221222211222221202102222222022121121221222202201222221202020222012221222211022222222222212200222120020122222222022022220220222220222201022022222222222221222201222121222201222220022002220221222212220222222212121222012220222220122221222222202200222111121222222222022122022222222222222002022222220022222220222212222122222022222221122210122221222212212222220212222222111220212002222221222220112221222121222222122222222122022221220222222012122112222022222222222210222121212022222222222122022221222202211222222222020222002222222121022221222222212212222011021122122222022122020020222220222201122112222022222220222211222120212022222222122010222221222202222222220202221222021222202021122222222221102202222222020220022222222122120121222222222202122112222122222222222222022021222202222220022021120221222222202222222212122222222220212001122221222222012202222111022022022222122222022122221221222211222022222202222221222202222122212201222220122201122222222212212222221202221222020221202021222220222222102212222102121120022222122122121122222222222201222102222212222221222222022121212121222222222122121221222202221222220222121222221221212112122221222221022221222210222120222222122122220120221220222202122212220012222220222212222020222212222221122222221222222202200222221202021222121221202001222222222220222222222002221121222222122122020121222221222101022002221012222220222201022221202022222222222101122221222202212222221212021222102222222102222220222221212202222011222121122222122022122220222221222200222122221002222222222222022220202002222022022020120221222202200222220202220222010220222002122222222212112210222122020020122222022020220021222222222022122122222222222220222200022222212011222121022002120220222212202222220202220222201222202100220221222202222222222000020022122222022220222220220212222210222102220022222220222220222021222102222020222221222220222212200222222202022222212221212020022222222222102202222101222121122222222122021020221212222201022012222202222222222211122021212022222022122210220222222212210222220202121222212222202122021121222210202210222000221121122222222122221122222222222200122212222002222221202201222022202001222121122002022222222222122222220202021222212020222221221020222220202220222021221220222222122121121121220222222222222122222202222222212221122021212210222120222222020220222212210222222202120222122021212002201221222202012211222101021122022222220220021121221222222002022012222122222222222210222222222202222021222100020221222222211222220222122222002021202022211022222210102200222120221221022222222020021122221202222112122202221012222221222202222120212111222021122011020222222212000222221222021222110121222022002220222200212210222011021222122222122021122122222211222200222102221202222221202201222122202122222020022122020220222202012222221202020222100122212210010222222210112212222111122121222222220021222021222202222021222012222012222220202202022220222112222220022212020221222202121222220222220222112222212211120021222201122220222022120120022222221222022220221221220101122002221112222222222220022222212221222222222202122222222222112222222212222222211122222112220221222201102211222021122222122222122120020120220202220121222112222022222221202211022121212201222022022220020221222222212222222202022222222020202001022022222221222221222112020020022222122122022220220211221100222022222002222220212220122020222211222021122200021222222202010222221202021222012021222100210220222211002211222001122022222222022120221122222210220020222122222102222221202210222220212100222120222010120220222202010222220202222222120020202211110120222212212201222022222021022222220022122122220210220221122122220022222220202220022222212112222122122002121222222202020222221202021222101021202120000221222212102212222201120121122222122022020122222220220200222122221112222221212201122120222121222021022010122221222202000222220202021222010122212202221222222212202220220220021122022222220222022022221212221122022012220222222222202220122222212001222120122012121222222222111222222222121222000221202002020122222212212201222001020021122222221222120121222202220012222212221002222220202202122120202221222122222221121220222212102222221202122022221122212202112021222210112221220000021221022222021021221120220210220121222002221112222220212211022221222220222212222212220220222222110222221222222022011222222010221220122210010200220020222021222222120221120020220220220001222012221112222222202211122121222001222120022100022222222102000222221202122122200020222120112121022201011220221010021021022222221121122021222200220221022002222102222222222222222221210022222012222101021222222022211122222202222122111122202210221121222220222022222101120122022222121121021121220212221111222202221102222200222200022121222200222022122200221222222012120222220222120121112012222100212022222210012211221121221222222222121120122122220221020200122022222012222211222201122021220102222210222222222220222122010122221202121022212212222021001121122201020211222011022022122222022220021122020222022001222102221122222211222211022021211202222220022222221221222002102222220222120020010000222110021122222201212112221211022122222222122020020121020220122002022012221021222200212220222122212102202220122202122220222102120022222002220220221120212020000222122220110200220201121220222222021122022221121210022001222112221212222201222210220122200022202210022021021221222210021022221022122221220210222202110021122220001201220111120121022222120220220121120220222121222222221211222212222201221221110200202101222100222221222220211222221202222021100102222210021120022222022222222201022120122222022220121120220202222220122112221010222212222201022121112000212220022002220220222101000022220102222221220112212211110122222211002011120012221221222222220020121221122211122002222202221021222221212222220222212001202220222100122221222202000022221022222121102201222020200220022221011102021200222220222222122021222120120212021121122012222010222202212202220221000102222211222220220221222102001122221122221122121112222021120122022200022001022100020021022222222121221022021202222111122212222110222210212212022121111202202111022011022220222200022222222222122122212012222201200121022212110121022210022121122222221122110120220222022220022002221012222210212200121022011100202011022011122222222020010022222212022022000011212001002022122222211121120022122122022222222020010221121212020022022102222012222201212221120220010020222111222022120222222010021122220012122121020100222102112120122210102101101211120020022222120021100022020212022210122012221221222210212210221122200221212211122202020221222022212222221102020220021000202220000121122221022120212120222121122222122221000121102201221011222212220221222200112212022222211000212221012112122221222012210222221022220221120202202212201020122200200012222201020021222222121020122122100202122220022202220220222212012220220022122212202002102210121122222210222122222122120220210011212120211021122212120000121201121122122222221020011022220220121120222222221100222221222111121120102112222102212102120122222221220222020002121222010022222212121122222222022221010220220121222222022121212222211222120111022212220100222212102011220120220102212211212122121222222012022222221012120022010100202222120222222222110210201110121220122222221020122120122212222120222022222111222200122121020221210201222012222120221021222211200222221022020021011202222220002122222201211122110000222210122222122020002222210200120122222112222221222201112201120022001212202120012102120020222101211222022222022122201011202110211120022221210001100120120212022222221022011022002220020102022002221120222200102110022020121202202102222122021021222200012122221112022221101222222100020221022200202102012222022010122222222020002020102221021022222212222221222221022200222222221002202001122021020222222122200222220210022121002210202000212021122200120100222201220211122222020121000122011220222001022022220212222210212020021121222010212210222000121122222002220022021122022021200011212110011020122201101102021101021211122222021121121121002211121121022202221111222221212002121121012121212210222122022021222101200022022020120222120020012101221021022201111221201120021112022222120122200021211212221002122112222002222210212011120122202220222010212012121022222201022222221002222122120120102112100220022210200122201222021120022222202221201120201211021122022120221210222200222111121210110102212100002201021220222121121202121022222121210022202212100021212212200210000020022011022222012121101120122220221210122110221012222212212020221121010102002002112220120220222020012102020011021022121021102212011121102012011202212121020200120222121221120221110201120110122012221110222212122020120220122021222011202201221021222011020102122121220021200102202120120122212211122210011120222200121222101122020221102200220022222000221100222201002010021220201022212220022120221021022210201002021211021020201102122221221121212000211021120020021110121221202221221122002201121010022222220121222210012101222201220020212101022001021120122022022112021102120121220101102122210220012122111021220001220202122222021120020120122202020110122112222120222220122122221112220100002112122221221021022000022202221021222222100111022010102020112001210010002021221120121221221122111222210200222022022111220021222221212101120010102111202002202021120020222012110112120110012120012022012101102121212000202212221211221102220221220222110021112202022101222102220100222222112002220021102212022111222202121122022222102212201222102222122212002212100020112200210011212100020102222220201022120220221202222001222101221112222210012101221122021200112100112222020020222001211202101222201020021210212102110220102201120201110021022101221222210022121121102222122220022221221101222200212221021201111121112010012222120120122012202022202212022022022201202020210120012100010011111012020120020220122021102021021201221101022200221120222220102111022020111000102120102100121222122012021212110220022222200202212212011102222121022021101122221211221220011020122122100200020211022112222101222201102112220122200202220101102002120122222120121012110001122210121102021101202201220110202102221002211022201102101002121202122121010001100212001120000110101201110120101110100111220201001012201120221121111001112"
+use crate::common::{puzzle_input, renderer};
+
+fn input() -> String {
+    puzzle_input(8)
 }
 
-struct Layer {
+pub struct Layer {
     pub width: usize,
     pub height: usize,
     pub pixels: Vec<u8>,
@@ -17,7 +19,7 @@ impl Layer {
     }
 }
 
-struct Bitmap {
+pub struct Bitmap {
     pub width: usize,
     pub height: usize,
     pub layers: Vec<Layer>,
@@ -97,8 +99,8 @@ pub fn test() {
     assert_eq!(bitmap.layers[1].pixels[5], 2);
 }
 
-pub fn part1() {
-    let bitmap = Bitmap::parse(input(), 25, 6);
+pub fn solve1(input: &str) -> usize {
+    let bitmap = Bitmap::parse(input, 25, 6);
 
     // Find the layer with the fewest 0's in it. Be cryptic and inefficient by not using a loop but using fold just because it exists
     let layer = bitmap
@@ -112,15 +114,29 @@ pub fn part1() {
             }
         });
 
+    layer.count_pixel_color(1) * layer.count_pixel_color(2)
+}
+
+/// The decoded image as text
+pub fn solve2(input: &str) -> String {
+    let mut bitmap = Bitmap::parse(input, 25, 6);
+    bitmap.flatten();
+
+    bitmap.to_string()
+}
+
+pub fn part1() {
     println!(
         "Number of 1's times 2's on layer with fewest 0's is {}",
-        layer.count_pixel_color(1) * layer.count_pixel_color(2)
+        solve1(&input())
     );
 }
 
 pub fn part2() {
-    let mut bitmap = Bitmap::parse(input(), 25, 6);
-    bitmap.flatten();
+    let image = solve2(&input());
+    println!("{}", image);
 
-    println!("{}", bitmap);
+    let mut renderer = renderer();
+    renderer.draw(|| image);
+    renderer.finish();
 }
@@ -1,4 +1,4 @@
-use crate::common::import_lines;
+use crate::common::puzzle_input;
 use crate::cpu::*;
 
 pub fn test() {
@@ -10,20 +10,25 @@ pub fn test() {
     execute(&mut program);
 }
 
-pub fn part1() {
-    let input = import_lines("src/day9/input.txt");
-
-    let mut program = read(&input);
-    program.add_input(1);
+fn boost(input: &str, mode: CpuWidth) -> Option<CpuWidth> {
+    let mut program = read(input);
+    program.add_input(mode);
     execute(&mut program);
-    println!("BOOST test mode output: {}", program.get_output(0).expect("Error, BOOST program TEST mode produced no output"))
+    program.get_output(0)
 }
 
-pub fn part2() {
-    let input = import_lines("src/day9/input.txt");
+pub fn solve1(input: &str) -> Option<CpuWidth> {
+    boost(input, 1)
+}
 
-    let mut program = read(&input);
-    program.add_input(2);
-    execute(&mut program);
-    println!("BOOST sensor mode output: {}", program.get_output(0).expect("Error, BOOST program SENSOR mode produced no output"))
+pub fn solve2(input: &str) -> Option<CpuWidth> {
+    boost(input, 2)
+}
+
+pub fn part1() {
+    println!("BOOST test mode output: {}", solve1(&puzzle_input(9)).expect("Error, BOOST program TEST mode produced no output"))
+}
+
+pub fn part2() {
+    println!("BOOST sensor mode output: {}", solve2(&puzzle_input(9)).expect("Error, BOOST program SENSOR mode produced no output"))
 }
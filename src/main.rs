@@ -1,3 +1,5 @@
+mod answers;
+mod bench;
 mod common;
 mod cpu;
 
@@ -19,7 +21,438 @@ mod day15;
 mod day16;
 mod day17;
 
+/// Writes the control flow graph of the Intcode program in the given file as Graphviz DOT to stdout
+/// e.g. cargo run -- cfg inputs/day15.txt | dot -Tsvg > droid.svg
+fn control_flow_graph(file: &str) {
+    let program = cpu::read(common::import_lines(file).trim());
+    let graph = cpu::cfg::ControlFlowGraph::build(&program);
+
+    print!("{}", graph.to_dot(&program));
+}
+
+/// Runs the Intcode program in the given file with the given input values and reports all self modifications
+/// e.g. cargo run -- smc inputs/day9.txt 1
+fn self_modification(file: &str, input: &[String]) {
+    let mut program = cpu::read(common::import_lines(file).trim());
+    program.detect_self_modification();
+    for value in input {
+        program.add_input(value.parse().expect("Input values must be integers"));
+    }
+
+    cpu::execute(&mut program);
+
+    for modification in program.self_modifications() {
+        println!("{}", modification);
+    }
+    println!(
+        "{} self modifications, program {}",
+        program.self_modifications().len(),
+        if program.is_finished() {
+            "finished"
+        } else {
+            "is waiting for input"
+        }
+    );
+}
+
+/// Dumps the memory of the Intcode program in the given file. Options:
+///     --format int|ascii|asm  how to render memory (default int)
+///     --range start..end      memory range to dump (default the loaded program)
+///     --run [input,...]       run the program first and report changed memory
+/// e.g. cargo run -- mem inputs/day9.txt --format asm --run 1
+fn memory_dump(file: &str, options: &[String]) {
+    let mut program = cpu::read(common::import_lines(file).trim());
+
+    let mut format = cpu::memory::Format::Integer;
+    let mut range = 0..program.size();
+    let mut run: Option<Vec<cpu::CpuWidth>> = None;
+
+    let mut options = options.iter().peekable();
+    while let Some(option) = options.next() {
+        match option.as_str() {
+            "--format" => {
+                format = options
+                    .next()
+                    .expect("--format requires a value")
+                    .parse()
+                    .unwrap_or_else(|e| panic!("{}", e));
+            }
+            "--range" => {
+                let value = options.next().expect("--range requires a value");
+                let bounds = value.split_once("..").and_then(|(start, end)| {
+                    Some((start.parse::<usize>().ok()?, end.parse::<usize>().ok()?))
+                });
+                range = match bounds {
+                    Some((start, end)) if start <= end => start..end,
+                    _ => panic!("Usage: --range start..end with start <= end, got {}", value),
+                };
+            }
+            "--run" => {
+                run = Some(
+                    options
+                        .next_if(|i| !i.starts_with("--"))
+                        .map(|i| {
+                            i.split(',')
+                                .map(|v| v.parse().expect("Input values must be integers"))
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                );
+            }
+            _ => panic!("Unknown option {}", option),
+        }
+    }
+
+    let before = program.snapshot();
+    if let Some(input) = run {
+        for value in input {
+            program.add_input(value);
+        }
+        cpu::execute(&mut program);
+    }
+
+    print!("{}", cpu::memory::dump(&program, range, format));
+
+    let changes = cpu::memory::diff(&before, &program.snapshot());
+    if !changes.is_empty() {
+        println!("Changed memory:");
+        for change in changes {
+            println!("{}", change);
+        }
+    }
+
+    let beyond = cpu::memory::beyond_program(&program);
+    if !beyond.is_empty() {
+        println!("Memory used beyond program end ({}):", program.size());
+        for (address, value) in beyond {
+            println!("{:6}: {}", address, value);
+        }
+    }
+}
+
+/// Downloads the inputs of the given days (default all days) that are not in the input directory yet.
+/// Needs the session token in ADVENT_SESSION, ADVENT_URL overrides the server
+/// e.g. ADVENT_SESSION=53616c74... cargo run -- fetch 9 13
+fn fetch_inputs(days: &[String]) {
+    let fetcher = common::fetch::Fetcher::from_env()
+        .unwrap_or_else(|| panic!("Set {} to the session token", common::fetch::SESSION_VAR));
+
+    let days = if days.is_empty() {
+        (1..=25).collect::<Vec<u32>>()
+    } else {
+        days.iter()
+            .map(|d| d.parse().expect("Days must be numbers"))
+            .collect()
+    };
+
+    for day in days {
+        match fetcher.ensure(day) {
+            Ok(path) => println!("Day {}: {}", day, path.display()),
+            Err(e) => println!("Day {}: {}", day, e),
+        }
+    }
+}
+
+/// Serves the inputs in the given directory like the puzzle server does, to try fetching offline
+/// e.g. cargo run -- serve inputs secret 8019 & ADVENT_URL=http://127.0.0.1:8019 ADVENT_SESSION=secret cargo run -- fetch
+fn serve_inputs(fixtures: &str, session: &str, port: Option<&String>) {
+    let port = port.map_or(0, |p| p.parse().expect("Port must be a number"));
+    let server = common::fetch::MockServer::start(std::path::Path::new(fixtures), session, port)
+        .expect("Could not start server");
+
+    println!("Serving {} on {}", fixtures, server.base_url());
+    loop {
+        std::thread::park();
+    }
+}
+
+/// Runs the solutions of the given days (default all days) and compares their answers with answers.txt.
+/// With --record the answers for inputs without a known answer are added to answers.txt
+/// e.g. cargo run --release -- verify 14 16
+fn verify_answers(options: &[String]) {
+    let record = options.iter().any(|o| o == "--record");
+    let days = options
+        .iter()
+        .filter(|o| *o != "--record")
+        .map(|d| d.parse().expect("Days must be numbers"))
+        .collect::<Vec<u32>>();
+
+    if !answers::verify(&days, record) {
+        std::process::exit(1);
+    }
+}
+
+/// Benchmarks the solutions of the given days (default all days) and writes the report as <output>.md and
+/// <output>.json (default benchmark). Options:
+///     --warmup N              runs before measuring (default 1)
+///     --iterations N          measured runs (default 5)
+///     --budget seconds        time after which a solution is measured no more (default 10)
+///     --output name           name of the report files
+///     --compare report.json   earlier report to compare with, regressions make the command fail
+///     --threshold percent     slowdown that counts as a regression (default 10)
+/// e.g. cargo run --release -- bench --compare benchmark.json --output after
+fn benchmark(options: &[String]) {
+    let mut settings = bench::Options::default();
+    let mut output = "benchmark".to_string();
+    let mut baseline = None;
+    let mut threshold = 10.0;
+    let mut days = Vec::new();
+
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        let mut value = || {
+            options
+                .next()
+                .unwrap_or_else(|| panic!("{} requires a value", option))
+        };
+        match option.as_str() {
+            "--warmup" => settings.warmup = value().parse().expect("Warm-up must be a number"),
+            "--iterations" => {
+                settings.iterations = value().parse().expect("Iterations must be a number")
+            }
+            "--budget" => {
+                settings.budget = std::time::Duration::from_secs_f64(
+                    value().parse().expect("Budget must be a number"),
+                )
+            }
+            "--output" => output = value().to_string(),
+            "--compare" => {
+                baseline = Some(
+                    bench::Report::read(std::path::Path::new(value()))
+                        .unwrap_or_else(|e| panic!("{}", e)),
+                )
+            }
+            "--threshold" => threshold = value().parse().expect("Threshold must be a number"),
+            day => days.push(
+                day.parse()
+                    .unwrap_or_else(|_| panic!("Unknown option {}", day)),
+            ),
+        }
+    }
+
+    let report = bench::run(&days, &settings);
+    let comparisons = baseline.map_or_else(Vec::new, |b| report.compare(&b, threshold));
+    let table = report.to_markdown(&comparisons);
+
+    print!("{}", table);
+    for (extension, content) in [("md", table), ("json", report.to_json())].iter() {
+        let path = format!("{}.{}", output, extension);
+        std::fs::write(&path, content)
+            .unwrap_or_else(|e| panic!("Could not write {}: {}", path, e));
+    }
+
+    let regressions = comparisons.iter().filter(|c| c.regression).count();
+    if regressions > 0 {
+        println!("{} regressions of more than {}%", regressions, threshold);
+        std::process::exit(1);
+    }
+}
+
+/// Replays a session recorded with ADVENT_RECORD on the Intcode program in the given file and reports where the
+/// replay differs from the recording
+/// e.g. ADVENT_RECORD=breakout.session cargo run, then cargo run -- replay inputs/day13.txt breakout.session
+fn replay_session(file: &str, recording: &str) {
+    let mut program = cpu::read(common::import_lines(file).trim());
+    let recorded = cpu::session::Session::read(std::path::Path::new(recording))
+        .unwrap_or_else(|e| panic!("{}", e));
+
+    let replayed =
+        cpu::session::replay(&mut program, &recorded).unwrap_or_else(|e| panic!("{}", e));
+    report_difference(&recorded, &replayed, "recorded", "replayed");
+}
+
+/// Compares two recorded sessions and reports the first event where they differ
+/// e.g. cargo run -- diff before.session after.session
+fn diff_sessions(a: &str, b: &str) {
+    let read = |file| {
+        cpu::session::Session::read(std::path::Path::new(file)).unwrap_or_else(|e| panic!("{}", e))
+    };
+    report_difference(&read(a), &read(b), a, b);
+}
+
+fn report_difference(
+    a: &cpu::session::Session,
+    b: &cpu::session::Session,
+    a_name: &str,
+    b_name: &str,
+) {
+    let event = |session: &cpu::session::Session, ix: usize| {
+        session
+            .events
+            .get(ix)
+            .map_or("end of session".to_string(), |e| e.to_string())
+    };
+
+    match a.diff(b) {
+        None => println!("Sessions are identical, {} events", a.events.len()),
+        Some(ix) => {
+            println!("Sessions differ at event {}", ix + 1);
+            println!("  {}: {}", a_name, event(a, ix));
+            println!("  {}: {}", b_name, event(b, ix));
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Prints the fuel of every module and the totals, streaming the masses from a file or stdin when there is none
+/// e.g. cargo run -- fuel inputs/day1.txt
+fn fuel_breakdown(file: Option<&String>) {
+    use std::io::BufRead;
+
+    let reader: Box<dyn BufRead> = match file {
+        Some(file) => Box::new(std::io::BufReader::new(
+            std::fs::File::open(file).unwrap_or_else(|e| panic!("Could not open {}: {}", file, e)),
+        )),
+        None => Box::new(std::io::BufReader::new(std::io::stdin())),
+    };
+
+    println!("{}", day1::fuel::BREAKDOWN_HEADER);
+    let mut totals = day1::fuel::Totals::default();
+    for module in day1::fuel::breakdown(reader) {
+        let module = module.unwrap_or_else(|e| panic!("{}", e));
+        println!("{}", module);
+        totals.add(&module).unwrap_or_else(|e| panic!("{}", e));
+    }
+    println!("{}", totals);
+}
+
+/// Prints the crossings of the wires in a file, one wire per line, closest first and optionally draws them
+/// e.g. cargo run -- wires inputs/day3.txt
+fn wire_crossings(file: &str, options: &[String]) {
+    let draw = match options {
+        [] => false,
+        [option] if option == "--draw" => true,
+        _ => panic!("Usage: wires <file> [--draw]"),
+    };
+
+    let wires = day3::read_wires(std::path::Path::new(file)).unwrap_or_else(|e| panic!("{}", e));
+    let crossings = day3::segments::crossings(&wires);
+    for c in crossings.iter() {
+        println!(
+            "{} wires {} and {}, distance {}, steps {}",
+            c.position,
+            c.wires.0 + 1,
+            c.wires.1 + 1,
+            c.distance(),
+            c.steps
+        );
+    }
+    println!("{} crossings of {} wires", crossings.len(), wires.len());
+
+    if draw {
+        print!("{}", day3::segments::render(&wires));
+    }
+}
+
+/// Counts the passwords in a range that pass the rules, see day4::rules for the rules, and optionally lists them
+/// e.g. cargo run -- passwords 100000-999999 length=6 non-decreasing group=3 forbid=0 --list
+fn count_passwords(range: &str, options: &[String]) {
+    let mut rules = day4::rules::RuleSet::new().with(day4::rules::Rule::Range(
+        day4::rules::parse_range(range).unwrap_or_else(|e| panic!("{}", e)),
+    ));
+    let mut list = false;
+    for option in options {
+        match option.as_str() {
+            "--list" => list = true,
+            rule => rules = rules.with(rule.parse().unwrap_or_else(|e| panic!("{}", e))),
+        }
+    }
+
+    // Without non-decreasing digits every number in the bounds is tried
+    const SEARCH_LIMIT: u64 = 100_000_000;
+    let bounds = rules.bounds();
+    if !rules.rules.contains(&day4::rules::Rule::NonDecreasing)
+        && bounds.end().saturating_sub(*bounds.start()) >= SEARCH_LIMIT
+    {
+        panic!(
+            "Refusing to try all numbers from {} to {}, narrow the range or add non-decreasing",
+            bounds.start(),
+            bounds.end()
+        );
+    }
+
+    if list {
+        for password in rules.candidates() {
+            println!("{}", password);
+        }
+    }
+    println!("{} passwords pass {}", rules.count(), rules);
+}
+
 fn main() {
+    let args = std::env::args().collect::<Vec<String>>();
+    match args.get(1).map(|a| a.as_str()) {
+        Some("bench") => {
+            benchmark(&args[2..]);
+            return;
+        }
+        Some("cfg") => {
+            control_flow_graph(args.get(2).expect("Usage: cfg <intcode file>"));
+            return;
+        }
+        Some("mem") => {
+            memory_dump(
+                args.get(2)
+                    .expect("Usage: mem <intcode file> [--format int|ascii|asm] [--range start..end] [--run input,...]"),
+                &args[3..],
+            );
+            return;
+        }
+        Some("fetch") => {
+            fetch_inputs(&args[2..]);
+            return;
+        }
+        Some("serve") => {
+            serve_inputs(
+                args.get(2).expect("Usage: serve <input dir> <session> [port]"),
+                args.get(3).expect("Usage: serve <input dir> <session> [port]"),
+                args.get(4),
+            );
+            return;
+        }
+        Some("verify") => {
+            verify_answers(&args[2..]);
+            return;
+        }
+        Some("replay") => {
+            replay_session(
+                args.get(2).expect("Usage: replay <intcode file> <session file>"),
+                args.get(3).expect("Usage: replay <intcode file> <session file>"),
+            );
+            return;
+        }
+        Some("diff") => {
+            diff_sessions(
+                args.get(2).expect("Usage: diff <session file> <session file>"),
+                args.get(3).expect("Usage: diff <session file> <session file>"),
+            );
+            return;
+        }
+        Some("wires") => {
+            wire_crossings(args.get(2).expect("Usage: wires <file> [--draw]"), &args[3..]);
+            return;
+        }
+        Some("passwords") => {
+            count_passwords(
+                args.get(2).expect("Usage: passwords <a-b> [rule...] [--list]"),
+                &args[3..],
+            );
+            return;
+        }
+        Some("fuel") => {
+            fuel_breakdown(args.get(2));
+            return;
+        }
+        Some("smc") => {
+            self_modification(
+                args.get(2).expect("Usage: smc <intcode file> [input...]"),
+                &args[3..],
+            );
+            return;
+        }
+        _ => {}
+    }
+
     // day1::part1();
     // day1::part2();
 